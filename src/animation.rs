@@ -0,0 +1,188 @@
+use std::{
+    io::{self, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::simulation::Simulation;
+use crate::utils::grid::{SparseGrid, SparsePos};
+use crate::utils::point::Point;
+
+/// A simulation drawn as a frame after each step, so it can be watched.
+pub type Animation<'a> = Box<dyn Simulation<State = Frame> + 'a>;
+
+/// What an animation shows at one step, and the cell to keep in view when it does not fit.
+#[derive(Clone, Debug, Default)]
+pub struct Frame {
+    pub caption: String,
+    pub cells: SparseGrid<char>,
+    pub focus: SparsePos,
+}
+
+impl Frame {
+    /// The caption and the cells within a `width` by `height` window, blank where none is set.
+    pub fn crop(&self, width: usize, height: usize) -> Vec<String> {
+        let mut lines = vec![self.caption.clone()];
+
+        let Some((min, max)) = self.cells.bounds() else {
+            return lines;
+        };
+
+        let window = Point::new(width as isize, height.saturating_sub(1) as isize);
+        let size = (max - min + Point::new(1, 1)).min(window);
+        let start = Point::new(
+            window_start(min.x, max.x, self.focus.x, size.x),
+            window_start(min.y, max.y, self.focus.y, size.y),
+        );
+
+        for y in start.y..start.y + size.y {
+            lines.push(
+                (start.x..start.x + size.x)
+                    .map(|x| self.cells.get(Point::new(x, y)).copied().unwrap_or(' '))
+                    .collect(),
+            );
+        }
+
+        lines
+    }
+}
+
+/// The first of `size` values within `min..=max` centred on `focus` as far as possible.
+fn window_start(min: isize, max: isize, focus: isize, size: isize) -> isize {
+    (focus - size / 2).clamp(min, (max - size + 1).max(min))
+}
+
+/// How an animation is played in the terminal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Playback {
+    pub fps: u32,
+    /// The number of steps between two frames.
+    pub speed: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            fps: 20,
+            speed: 1,
+            width: 80,
+            height: 24,
+        }
+    }
+}
+
+/// Plays `animation` until it is done, redrawing each frame over the previous one.
+pub fn play<W: Write>(
+    animation: &mut dyn Simulation<State = Frame>,
+    playback: &Playback,
+    out: &mut W,
+) -> io::Result<()> {
+    let delay = Duration::from_secs_f64(1.0 / playback.fps.max(1) as f64);
+    let mut drawn = 0;
+
+    loop {
+        let started = Instant::now();
+        let lines = animation.state().crop(playback.width, playback.height);
+
+        // Back to the start of the previous frame, then clear whatever it left behind
+        let mut screen = match drawn {
+            0 => String::new(),
+            lines => format!("\x1b[{lines}A\r"),
+        };
+
+        for line in &lines {
+            screen.push_str(line);
+            screen.push_str("\x1b[K\n");
+        }
+
+        screen.push_str("\x1b[J");
+        out.write_all(screen.as_bytes())?;
+        out.flush()?;
+        drawn = lines.len();
+
+        if animation.is_done() {
+            return Ok(());
+        }
+
+        for _ in 0..playback.speed {
+            if animation.is_done() {
+                break;
+            }
+
+            animation.step();
+        }
+
+        thread::sleep(delay.saturating_sub(started.elapsed()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter(isize);
+
+    impl Simulation for Counter {
+        type State = Frame;
+
+        fn step(&mut self) {
+            self.0 += 1;
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 3
+        }
+
+        fn state(&self) -> Frame {
+            let mut cells = SparseGrid::new();
+            cells.insert(Point::new(self.0, 0), '#');
+
+            Frame {
+                caption: format!("at {}", self.0),
+                cells,
+                focus: Point::new(self.0, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn crops_around_the_focus() {
+        let mut cells = SparseGrid::new();
+
+        for (x, char) in "abcdefgh".chars().enumerate() {
+            cells.insert(Point::new(x as isize, 0), char);
+            cells.insert(Point::new(x as isize, 1), char.to_ascii_uppercase());
+        }
+
+        let frame = |focus| Frame {
+            caption: "caption".to_owned(),
+            cells: cells.clone(),
+            focus,
+        };
+
+        assert_eq!(frame(Point::new(5, 0)).crop(3, 2), ["caption", "efg"]);
+        assert_eq!(frame(Point::new(7, 1)).crop(4, 2), ["caption", "EFGH"]);
+        assert_eq!(
+            frame(Point::new(0, 0)).crop(20, 5),
+            ["caption", "abcdefgh", "ABCDEFGH"]
+        );
+    }
+
+    #[test]
+    fn redraws_every_frame_in_place() {
+        let mut out = vec![];
+        let playback = Playback {
+            fps: 1000,
+            ..Playback::default()
+        };
+
+        play(&mut Counter(0), &playback, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(out.matches("\x1b[2A\r").count(), 3);
+        assert!(out.ends_with("at 3\x1b[K\n#\x1b[K\n\x1b[J"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::solution::Answer;
+
+#[derive(Debug)]
+pub struct Answers {
+    known: HashMap<(usize, usize), Answer>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail(Answer),
+    Unknown,
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        let content = read_to_string(path).map_err(|err| AnswersError::Io(path.to_owned(), err))?;
+
+        Self::parse(&content)
+    }
+
+    /// Parses the subset of TOML the answers file needs:
+    ///
+    /// ```toml
+    /// [day_01]
+    /// part_1 = 24000
+    /// part_2 = "CMZ"
+    /// ```
+    pub fn parse(content: &str) -> Result<Self, AnswersError> {
+        let mut known = HashMap::new();
+        let mut day = None;
+
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let invalid = |message: &str| AnswersError::Invalid(line_number, message.to_owned());
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let number = section
+                    .strip_prefix("day_")
+                    .and_then(|number| number.parse::<usize>().ok())
+                    .ok_or_else(|| invalid("expected a section like [day_01]"))?;
+
+                day = Some(number);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected 'part_N = answer'"))?;
+            let day = day.ok_or_else(|| invalid("answer outside of a [day_NN] section"))?;
+            let part = match key.trim() {
+                "part_1" => 1,
+                "part_2" => 2,
+                _ => return Err(invalid("expected part_1 or part_2")),
+            };
+
+            known.insert((day, part), parse_value(value.trim()).map_err(invalid)?);
+        }
+
+        Ok(Self { known })
+    }
+
+    /// Compares the typed answers, so a number never passes for a string that reads the same.
+    pub fn check(&self, day: usize, part: usize, answer: &Answer) -> Verdict {
+        match self.known.get(&(day, part)) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail(expected.clone()),
+        }
+    }
+}
+
+fn parse_value(value: &str) -> Result<Answer, &'static str> {
+    if let Ok(number) = value.parse::<u64>() {
+        return Ok(Answer::Number(number));
+    }
+
+    let quoted = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or("expected a number or a quoted string")?;
+
+    let mut text = String::new();
+    let mut chars = quoted.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            text.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('"') => text.push('"'),
+            Some('\\') => text.push('\\'),
+            _ => return Err("unsupported escape sequence"),
+        }
+    }
+
+    Ok(Answer::Text(text))
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(PathBuf, io::Error),
+    Invalid(usize, String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "could not read '{}': {err}", path.display()),
+            Self::Invalid(line, message) => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numbers_and_strings() {
+        let answers = Answers::parse(
+            "# comment\n[day_01]\npart_1 = 24000\n\n[day_05]\npart_2 = \"MCD\"\n[day_10]\npart_2 = \"#.\\n.#\"",
+        )
+        .unwrap();
+
+        assert_eq!(answers.check(1, 1, &Answer::Number(24000)), Verdict::Pass);
+        assert_eq!(answers.check(5, 2, &Answer::from("MCD")), Verdict::Pass);
+        assert_eq!(answers.check(10, 2, &Answer::from("#.\n.#")), Verdict::Pass);
+        assert_eq!(
+            answers.check(1, 1, &Answer::Number(1)),
+            Verdict::Fail(Answer::Number(24000))
+        );
+        assert_eq!(answers.check(1, 2, &Answer::Number(1)), Verdict::Unknown);
+    }
+
+    #[test]
+    fn compares_typed_answers() {
+        let answers = Answers::parse("[day_01]\npart_1 = 42").unwrap();
+
+        assert_eq!(
+            answers.check(1, 1, &Answer::from("42")),
+            Verdict::Fail(Answer::Number(42))
+        );
+    }
+
+    #[test]
+    fn reports_the_invalid_line() {
+        let err = Answers::parse("[day_01]\npart_3 = 1").unwrap_err();
+
+        assert_eq!(err.to_string(), "line 2: expected part_1 or part_2");
+    }
+}
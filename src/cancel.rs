@@ -0,0 +1,72 @@
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::error::SolveError;
+
+/// A flag shared with a running part, set once nobody waits for its answer anymore.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `token` as the one [`check`] looks at on this thread.
+pub fn with_token<T>(token: CancelToken, f: impl FnOnce() -> T) -> T {
+    let previous = CURRENT.replace(Some(token));
+    let output = f();
+    CURRENT.set(previous);
+
+    output
+}
+
+/// Fails once the part solved on this thread is cancelled, so long loops can stop early.
+///
+/// Nothing else stops a part: one that never calls this runs to its end even after timing out.
+pub fn check() -> Result<(), SolveError> {
+    let cancelled =
+        CURRENT.with_borrow(|token| token.as_ref().is_some_and(CancelToken::is_cancelled));
+
+    if cancelled {
+        Err(SolveError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_the_token_of_the_current_thread() {
+        let token = CancelToken::new();
+
+        assert_eq!(check(), Ok(()));
+        assert_eq!(with_token(token.clone(), check), Ok(()));
+
+        token.cancel();
+
+        assert_eq!(with_token(token, check), Err(SolveError::Cancelled));
+        assert_eq!(check(), Ok(()));
+    }
+}
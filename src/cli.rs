@@ -0,0 +1,544 @@
+use std::{fmt, path::PathBuf, time::Duration};
+
+use itertools::Itertools;
+
+use crate::animation::Playback;
+use crate::params::Overrides;
+use crate::pool::default_jobs;
+use crate::report::Format;
+use crate::trace::{Filter, FilterError};
+use crate::utils::image::ImageFormat;
+use crate::utils::Input;
+
+pub const USAGE: &str = "Usage: aoc-2022-rust [run] [DAYS] [OPTIONS]
+
+Commands:
+  run      Run solutions for the selected days (default)
+  verify   Check answers against the known answers file
+  bench    Run each part several times and report min/median/max times
+  list     List the available days
+  help     Print this message
+
+Days:
+  all      Every available day (default)
+  7        A single day
+  3..9     A range of days, end exclusive (use 3..=9 to include 9)
+  1,5,7    A comma separated list of any of the above
+
+Options:
+  -p, --part PART       Run only part 1 or part 2
+  -i, --input FILE      Read the input from FILE, or from stdin if FILE is '-'
+      --input-dir DIR   Read inputs from DIR/NN.txt (default: $AOC_INPUT_DIR or 'inputs')
+      --answers FILE    Known answers used by verify (default: 'answers.toml')
+      --param KEY=VALUE Override a puzzle parameter of the selected days, e.g. y=10
+      --time            Report the input load time and the time taken by each part
+      --format FORMAT   Print results as 'text' (default) or 'json', one object per line
+      --render FORMAT   Draw the days that have a picture as 'ppm', 'png' or 'svg' files
+      --render-dir DIR  Write the pictures to DIR/NN.FORMAT (default: 'renders')
+      --animate         Play the days that can be watched step by step in the terminal
+      --fps N           Frames per second of the animations (default: 20)
+      --speed N         Steps the animations advance per frame (default: 1)
+      --viewport WxH    Crop the animations to W columns and H lines (default: 80x24)
+      --timeout SECS    Report a part still running after SECS seconds as TIMEOUT and move on
+  -j, --jobs N          Solve up to N parts at once, still reported in order (default: one per core)
+      --runs N          Number of runs per part used by bench (default: 10)
+  -v, --verbose         Print diagnostics to stderr, repeat for more (-vv, -vvv)
+      --log FILTER      Choose the diagnostics shown, e.g. 'info,day_16=trace' (default: $RUST_LOG)
+  -h, --help            Print this message";
+
+const DEFAULT_ANSWERS: &str = "answers.toml";
+const DEFAULT_RUNS: usize = 10;
+const DEFAULT_RENDER_DIR: &str = "renders";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(RunArgs),
+    Verify(RunArgs, PathBuf),
+    Bench(RunArgs, usize),
+    List,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+    pub days: Vec<usize>,
+    pub part: Option<usize>,
+    pub input: Option<Input>,
+    pub time: bool,
+    pub format: Format,
+    pub params: Overrides,
+    pub render: Option<Render>,
+    pub animate: Option<Playback>,
+    /// The number of parts solved at once.
+    pub jobs: usize,
+    /// How long a part may run before it is given up on.
+    pub timeout: Option<Duration>,
+    pub verbosity: usize,
+    pub log: Option<Filter>,
+}
+
+impl RunArgs {
+    pub fn parts(&self) -> Vec<usize> {
+        match self.part {
+            Some(part) => vec![part],
+            None => vec![1, 2],
+        }
+    }
+}
+
+/// Where and how to write the pictures of the solved days.
+#[derive(Debug, PartialEq)]
+pub struct Render {
+    pub format: ImageFormat,
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownCommand(String),
+    UnexpectedArgument(String),
+    UnsupportedOption(&'static str, &'static str),
+    MissingValue(&'static str),
+    InvalidDay(String),
+    UnknownDay(usize, Vec<usize>),
+    EmptyRange(String),
+    InvalidPart(String),
+    InvalidRuns(String),
+    InvalidFormat(String),
+    InvalidParam(String),
+    InvalidRenderFormat(String),
+    InvalidValue(&'static str, String),
+    MissingOption(&'static str, &'static str),
+    InvalidLog(FilterError),
+    SingleInputForManyDays,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => write!(f, "unknown command '{command}'"),
+            Self::UnexpectedArgument(arg) => write!(f, "unexpected argument '{arg}'"),
+            Self::UnsupportedOption(option, command) => {
+                write!(f, "option '{option}' is not supported by '{command}'")
+            }
+            Self::MissingValue(option) => write!(f, "option '{option}' requires a value"),
+            Self::InvalidDay(day) => write!(f, "'{day}' is not a valid day selection"),
+            Self::UnknownDay(day, available) => write!(
+                f,
+                "day {day} is not solved yet, available days: {}",
+                available.iter().join(", ")
+            ),
+            Self::EmptyRange(range) => write!(f, "no solved days in range '{range}'"),
+            Self::InvalidPart(part) => write!(f, "'{part}' is not a valid part, expected 1 or 2"),
+            Self::InvalidRuns(runs) => write!(f, "'{runs}' is not a valid number of runs"),
+            Self::InvalidFormat(format) => {
+                write!(f, "'{format}' is not a valid format, expected text or json")
+            }
+            Self::InvalidParam(param) => {
+                write!(f, "'{param}' is not a valid parameter, expected KEY=VALUE")
+            }
+            Self::InvalidRenderFormat(format) => {
+                write!(
+                    f,
+                    "'{format}' is not a valid image format, expected ppm, png or svg"
+                )
+            }
+            Self::InvalidValue(option, value) => {
+                write!(f, "'{value}' is not a valid value for '{option}'")
+            }
+            Self::MissingOption(option, required) => {
+                write!(f, "option '{option}' requires '{required}'")
+            }
+            Self::InvalidLog(err) => write!(f, "invalid '--log' filter: {err}"),
+            Self::SingleInputForManyDays => {
+                write!(
+                    f,
+                    "'--input' can only be used when a single day is selected"
+                )
+            }
+        }
+    }
+}
+
+pub fn parse<I>(args: I, available_days: &[usize]) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+
+    let command = match args.peek().map(|arg| arg.as_str()) {
+        Some("run") => {
+            args.next();
+            "run"
+        }
+        Some("verify") => {
+            args.next();
+            "verify"
+        }
+        Some("bench") => {
+            args.next();
+            "bench"
+        }
+        Some("list") => return Ok(Command::List),
+        Some("help" | "-h" | "--help") => return Ok(Command::Help),
+        Some(arg) if !arg.starts_with('-') && !is_day_selection(arg) => {
+            return Err(CliError::UnknownCommand(arg.to_owned()))
+        }
+        _ => "run",
+    };
+
+    let mut days = None;
+    let mut part = None;
+    let mut input = None;
+    let mut answers = None;
+    let mut time = false;
+    let mut runs = None;
+    let mut format = Format::default();
+    let mut params = Overrides::default();
+    let mut render_format = None;
+    let mut render_dir = None;
+    let mut animate = false;
+    let mut playback = Playback::default();
+    let mut playback_option = None;
+    let mut jobs = None;
+    let mut timeout = None;
+    let mut verbosity = 0;
+    let mut log = None;
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => {
+                (name.to_owned(), Some(value.to_owned()))
+            }
+            _ => (arg.clone(), None),
+        };
+
+        let mut value = |option| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or(CliError::MissingValue(option))
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-p" | "--part" => part = Some(parse_part(&value("--part")?)?),
+            "-i" | "--input" => {
+                input = Some(match value("--input")?.as_str() {
+                    "-" => Input::Stdin,
+                    path => Input::File(PathBuf::from(path)),
+                });
+            }
+            "--input-dir" => input = Some(Input::Dir(PathBuf::from(value("--input-dir")?))),
+            "--answers" if command == "verify" => {
+                answers = Some(PathBuf::from(value("--answers")?));
+            }
+            "--answers" => return Err(CliError::UnsupportedOption("--answers", command)),
+            "--param" => {
+                let param = value("--param")?;
+                let (key, value) = param
+                    .split_once('=')
+                    .ok_or_else(|| CliError::InvalidParam(param.clone()))?;
+
+                params.push(key, value);
+            }
+            "--time" if command == "run" => time = true,
+            "--time" => return Err(CliError::UnsupportedOption("--time", command)),
+            "--format" if command == "run" => format = parse_format(&value("--format")?)?,
+            "--format" => return Err(CliError::UnsupportedOption("--format", command)),
+            "--render" if command == "run" => {
+                render_format = Some(parse_render_format(&value("--render")?)?);
+            }
+            "--render" => return Err(CliError::UnsupportedOption("--render", command)),
+            "--render-dir" if command == "run" => {
+                render_dir = Some(PathBuf::from(value("--render-dir")?));
+            }
+            "--render-dir" => return Err(CliError::UnsupportedOption("--render-dir", command)),
+            "--animate" if command == "run" => animate = true,
+            "--animate" => return Err(CliError::UnsupportedOption("--animate", command)),
+            "--fps" => {
+                playback.fps = parse_positive("--fps", &value("--fps")?)?;
+                playback_option = Some("--fps");
+            }
+            "--speed" => {
+                playback.speed = parse_positive("--speed", &value("--speed")?)?;
+                playback_option = Some("--speed");
+            }
+            "--viewport" => {
+                (playback.width, playback.height) = parse_viewport(&value("--viewport")?)?;
+                playback_option = Some("--viewport");
+            }
+            "-v" | "--verbose" => verbosity += 1,
+            _ if is_verbosity(&arg) => verbosity += arg.len() - 1,
+            "--log" => {
+                log = Some(value("--log")?.parse().map_err(CliError::InvalidLog)?);
+            }
+            "-j" | "--jobs" if command != "bench" => {
+                jobs = Some(parse_positive("--jobs", &value("--jobs")?)?);
+            }
+            "-j" | "--jobs" => return Err(CliError::UnsupportedOption("--jobs", command)),
+            "--timeout" if command != "bench" => {
+                let value = value("--timeout")?;
+                let seconds: f64 = parse_positive("--timeout", &value)?;
+
+                timeout = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| CliError::InvalidValue("--timeout", value))?,
+                );
+            }
+            "--timeout" => return Err(CliError::UnsupportedOption("--timeout", command)),
+            "--runs" if command == "bench" => runs = Some(parse_runs(&value("--runs")?)?),
+            "--runs" => return Err(CliError::UnsupportedOption("--runs", command)),
+            _ if days.is_none() && !arg.starts_with('-') => {
+                days = Some(parse_days(&arg, available_days)?);
+            }
+            _ => return Err(CliError::UnexpectedArgument(arg)),
+        }
+    }
+
+    let days = days.unwrap_or_else(|| available_days.to_vec());
+
+    if matches!(input, Some(Input::File(_) | Input::Stdin)) && days.len() != 1 {
+        return Err(CliError::SingleInputForManyDays);
+    }
+
+    let render = match (render_format, render_dir) {
+        (Some(format), dir) => Some(Render {
+            format,
+            dir: dir.unwrap_or_else(|| PathBuf::from(DEFAULT_RENDER_DIR)),
+        }),
+        (None, Some(_)) => return Err(CliError::MissingOption("--render-dir", "--render")),
+        (None, None) => None,
+    };
+
+    let animate = match (animate, playback_option) {
+        (true, _) => Some(playback),
+        (false, Some(option)) => return Err(CliError::MissingOption(option, "--animate")),
+        (false, None) => None,
+    };
+
+    let args = RunArgs {
+        days,
+        part,
+        input,
+        time,
+        format,
+        params,
+        render,
+        animate,
+        jobs: jobs.unwrap_or_else(default_jobs),
+        timeout,
+        verbosity,
+        log,
+    };
+
+    Ok(match command {
+        "verify" => Command::Verify(
+            args,
+            answers.unwrap_or_else(|| PathBuf::from(DEFAULT_ANSWERS)),
+        ),
+        "bench" => Command::Bench(args, runs.unwrap_or(DEFAULT_RUNS)),
+        _ => Command::Run(args),
+    })
+}
+
+fn is_day_selection(arg: &str) -> bool {
+    arg == "all" || arg.starts_with(|char: char| char.is_ascii_digit())
+}
+
+/// A repeated short verbose flag, e.g. `-vvv`.
+fn is_verbosity(arg: &str) -> bool {
+    arg.len() > 2 && arg.starts_with('-') && arg[1..].bytes().all(|byte| byte == b'v')
+}
+
+fn parse_part(value: &str) -> Result<usize, CliError> {
+    match value {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(CliError::InvalidPart(value.to_owned())),
+    }
+}
+
+fn parse_format(value: &str) -> Result<Format, CliError> {
+    match value {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(CliError::InvalidFormat(value.to_owned())),
+    }
+}
+
+fn parse_render_format(value: &str) -> Result<ImageFormat, CliError> {
+    match value {
+        "ppm" => Ok(ImageFormat::Ppm),
+        "png" => Ok(ImageFormat::Png),
+        "svg" => Ok(ImageFormat::Svg),
+        _ => Err(CliError::InvalidRenderFormat(value.to_owned())),
+    }
+}
+
+fn parse_positive<T: std::str::FromStr + Default + PartialOrd>(
+    option: &'static str,
+    value: &str,
+) -> Result<T, CliError> {
+    value
+        .parse::<T>()
+        .ok()
+        .filter(|number| *number > T::default())
+        .ok_or_else(|| CliError::InvalidValue(option, value.to_owned()))
+}
+
+/// A `WxH` size, e.g. `120x40`.
+fn parse_viewport(value: &str) -> Result<(usize, usize), CliError> {
+    let invalid = || CliError::InvalidValue("--viewport", value.to_owned());
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+
+    Ok((
+        parse_positive("--viewport", width).map_err(|_| invalid())?,
+        parse_positive("--viewport", height).map_err(|_| invalid())?,
+    ))
+}
+
+fn parse_runs(value: &str) -> Result<usize, CliError> {
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|runs| *runs > 0)
+        .ok_or_else(|| CliError::InvalidRuns(value.to_owned()))
+}
+
+fn parse_days(selection: &str, available_days: &[usize]) -> Result<Vec<usize>, CliError> {
+    if selection == "all" {
+        return Ok(available_days.to_vec());
+    }
+
+    let mut days = vec![];
+
+    for item in selection.split(',') {
+        let invalid = || CliError::InvalidDay(item.to_owned());
+
+        if let Some((from, to)) = item.split_once("..") {
+            let from = from.parse::<usize>().map_err(|_| invalid())?;
+            let to = match to.strip_prefix('=') {
+                Some(to) => to.parse::<usize>().map_err(|_| invalid())?,
+                None => to
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|to| to.checked_sub(1))
+                    .ok_or_else(invalid)?,
+            };
+
+            if from > to {
+                return Err(invalid());
+            }
+
+            // Unsolved days inside a range are skipped instead of reported
+            let in_range = available_days
+                .iter()
+                .filter(|day| (from..=to).contains(*day))
+                .collect_vec();
+
+            if in_range.is_empty() {
+                return Err(CliError::EmptyRange(item.to_owned()));
+            }
+
+            days.extend(in_range);
+        } else {
+            let day = item.parse::<usize>().map_err(|_| invalid())?;
+
+            if !available_days.contains(&day) {
+                return Err(CliError::UnknownDay(day, available_days.to_vec()));
+            }
+
+            days.push(day);
+        }
+    }
+
+    Ok(days.into_iter().unique().collect_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAYS: [usize; 5] = [1, 2, 3, 5, 7];
+
+    fn parse_args(args: &str) -> Result<Command, CliError> {
+        parse(args.split_whitespace().map(str::to_owned), &DAYS)
+    }
+
+    fn days(args: &str) -> Result<Vec<usize>, CliError> {
+        match parse_args(args)? {
+            Command::Run(args) => Ok(args.days),
+            command => panic!("expected a run, got {command:?}"),
+        }
+    }
+
+    #[test]
+    fn selects_days() {
+        assert_eq!(days(""), Ok(DAYS.to_vec()));
+        assert_eq!(days("all"), Ok(DAYS.to_vec()));
+        assert_eq!(days("run 5"), Ok(vec![5]));
+        assert_eq!(days("3..7"), Ok(vec![3, 5]));
+        assert_eq!(days("3..=7"), Ok(vec![3, 5, 7]));
+        assert_eq!(days("0..3"), Ok(vec![1, 2]));
+        assert_eq!(days("7,1..3,2"), Ok(vec![7, 1, 2]));
+    }
+
+    #[test]
+    fn rejects_bad_day_selections() {
+        assert_eq!(days("5..3"), Err(CliError::InvalidDay("5..3".to_owned())));
+        assert_eq!(days("0..0"), Err(CliError::InvalidDay("0..0".to_owned())));
+        assert_eq!(days("3..x"), Err(CliError::InvalidDay("3..x".to_owned())));
+        assert_eq!(days("4..5"), Err(CliError::EmptyRange("4..5".to_owned())));
+        assert_eq!(days("6"), Err(CliError::UnknownDay(6, DAYS.to_vec())));
+        assert_eq!(
+            parse_args("nonsense"),
+            Err(CliError::UnknownCommand("nonsense".to_owned()))
+        );
+        assert_eq!(
+            parse_args("run 1 2"),
+            Err(CliError::UnexpectedArgument("2".to_owned()))
+        );
+    }
+
+    #[test]
+    fn selects_parts() {
+        let parts = |args| match parse_args(args) {
+            Ok(Command::Run(args)) => Ok(args.parts()),
+            Ok(command) => panic!("expected a run, got {command:?}"),
+            Err(err) => Err(err),
+        };
+
+        assert_eq!(parts("1"), Ok(vec![1, 2]));
+        assert_eq!(parts("1 --part 2"), Ok(vec![2]));
+        assert_eq!(parts("-p 1 1"), Ok(vec![1]));
+        assert_eq!(parts("1 --part=2"), Ok(vec![2]));
+        assert_eq!(
+            parts("1 --part 3"),
+            Err(CliError::InvalidPart("3".to_owned()))
+        );
+        assert_eq!(parts("1 --part"), Err(CliError::MissingValue("--part")));
+    }
+
+    #[test]
+    fn parses_timeouts() {
+        let timeout = |args: &str| match parse_args(args) {
+            Ok(Command::Run(args)) => Ok(args.timeout),
+            Ok(command) => panic!("expected a run, got {command:?}"),
+            Err(err) => Err(err),
+        };
+
+        assert_eq!(timeout("1"), Ok(None));
+        assert_eq!(
+            timeout("1 --timeout 0.5"),
+            Ok(Some(Duration::from_millis(500)))
+        );
+
+        for value in ["0", "-1", "inf", "1e30", "NaN"] {
+            assert_eq!(
+                timeout(&format!("1 --timeout {value}")),
+                Err(CliError::InvalidValue("--timeout", value.to_owned()))
+            );
+        }
+    }
+}
@@ -1,31 +1,77 @@
-use crate::utils::read_lines;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::{parse_number, parse_records};
 
-pub fn part_1() -> u32 {
-    let lines = read_lines(1);
-    split_by_elfs(&lines).into_iter().max().unwrap()
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 1;
+    const TITLE: &'static str = "Calorie Counting";
+
+    type Parsed = Vec<u32>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(elfs: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(elfs).map(Answer::from)
+    }
+
+    fn part_2(elfs: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(elfs).map(Answer::from)
+    }
 }
 
-pub fn part_2() -> u32 {
-    let mut elfs = split_by_elfs(&read_lines(1));
+pub fn part_1(elfs: &[u32]) -> Result<u32, SolveError> {
+    Ok(elfs.iter().copied().max().unwrap_or(0))
+}
+
+pub fn part_2(elfs: &[u32]) -> Result<u32, SolveError> {
+    let mut elfs = elfs.to_vec();
     elfs.sort();
     elfs.reverse();
-    elfs.into_iter().take(3).sum()
+    Ok(elfs.into_iter().take(3).sum())
+}
+
+/// The calories carried by every elf.
+pub fn parse(input: &str) -> Result<Vec<u32>, ParseError> {
+    parse_records(input, |elf| {
+        Ok(elf
+            .parse_lines(|line| parse_number::<u32>(line, line))?
+            .into_iter()
+            .sum())
+    })
 }
 
-fn split_by_elfs(lines: &Vec<String>) -> Vec<u32> {
-    let mut elfs = vec![];
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut food = 0;
-    for line in lines {
-        if line.is_empty() {
-            elfs.push(food);
-            food = 0;
+    const EXAMPLE: &str = "\
+1000
+2000
+3000
 
-            continue;
-        }
+4000
 
-        food += line.parse::<u32>().unwrap();
+5000
+6000
+
+7000
+8000
+9000
+
+10000";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(24000));
     }
 
-    elfs
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(45000));
+    }
 }
@@ -1,47 +1,70 @@
-use regex::Regex;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::parse::complete;
+use crate::utils::parse_lines;
 
-use crate::utils::read_lines;
+pub struct Day;
 
-pub fn part_1() -> u32 {
-    read_lines(2)
-        .iter()
-        .map(|line| score(parse_line(&line)))
-        .sum()
+impl Puzzle for Day {
+    const DAY: usize = 2;
+    const TITLE: &'static str = "Rock Paper Scissors";
+
+    type Parsed = Vec<Round>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(rounds: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(rounds).map(Answer::from)
+    }
+
+    fn part_2(rounds: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(rounds).map(Answer::from)
+    }
+}
+
+pub fn part_1(rounds: &[Round]) -> Result<u32, SolveError> {
+    Ok(rounds.iter().cloned().map(score).sum())
+}
+
+pub fn part_2(rounds: &[Round]) -> Result<u32, SolveError> {
+    Ok(rounds.iter().cloned().map(score_updated).sum())
 }
 
-pub fn part_2() -> u32 {
-    read_lines(2)
-        .iter()
-        .map(|line| score_updated(parse_line(&line)))
-        .sum()
+pub fn parse(input: &str) -> Result<Vec<Round>, ParseError> {
+    parse_lines(input, parse_line)
 }
 
-#[derive(Clone, PartialEq)]
-enum Shape {
+/// The opponent's shape and the second column, read as a shape.
+pub type Round = (Shape, Shape);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
     Rock,
     Paper,
     Scissors,
 }
 
-fn parse_line(line: &String) -> (Shape, Shape) {
-    let temlate = Regex::new(r"^(\w) (\w)$").unwrap();
-    let captures = temlate.captures(line).unwrap();
+fn parse_line(line: &str) -> Result<Round, ParseError> {
+    complete(line, |cursor| {
+        let opponent_shape = match cursor.one_of(&["A", "B", "C"])? {
+            "A" => Shape::Rock,
+            "B" => Shape::Paper,
+            _ => Shape::Scissors,
+        };
 
-    let opponent_shape = match &captures[1] {
-        "A" => Shape::Rock,
-        "B" => Shape::Paper,
-        "C" => Shape::Scissors,
-        _ => unreachable!(),
-    };
+        cursor.tag(" ")?;
 
-    let my_shape = match &captures[2] {
-        "X" => Shape::Rock,
-        "Y" => Shape::Paper,
-        "Z" => Shape::Scissors,
-        _ => unreachable!(),
-    };
+        let my_shape = match cursor.one_of(&["X", "Y", "Z"])? {
+            "X" => Shape::Rock,
+            "Y" => Shape::Paper,
+            _ => Shape::Scissors,
+        };
 
-    (opponent_shape, my_shape)
+        Ok((opponent_shape, my_shape))
+    })
 }
 
 const LOST: u32 = 0;
@@ -67,9 +90,9 @@ fn score((opponent_shape, my_shape): (Shape, Shape)) -> u32 {
 }
 
 enum Strategy {
-    ToLose,
-    ToDraw,
-    ToWin,
+    Lose,
+    Draw,
+    Win,
 }
 
 fn score_updated((opponent_shape, my_shape): (Shape, Shape)) -> u32 {
@@ -77,17 +100,37 @@ fn score_updated((opponent_shape, my_shape): (Shape, Shape)) -> u32 {
     use Strategy::*;
 
     let strategy = match my_shape {
-        Rock => ToLose,
-        Paper => ToDraw,
-        Scissors => ToWin,
+        Rock => Lose,
+        Paper => Draw,
+        Scissors => Win,
     };
 
     let my_shape = match (&opponent_shape, strategy) {
-        (Rock, ToLose) | (Paper, ToWin) => Scissors,
-        (Rock, ToWin) | (Scissors, ToLose) => Paper,
-        (Paper, ToLose) | (Scissors, ToWin) => Rock,
-        (shape, ToDraw) => shape.clone(),
+        (Rock, Lose) | (Paper, Win) => Scissors,
+        (Rock, Win) | (Scissors, Lose) => Paper,
+        (Paper, Lose) | (Scissors, Win) => Rock,
+        (shape, Draw) => shape.clone(),
     };
 
     score((opponent_shape, my_shape))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+A Y
+B X
+C Z";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(15));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(12));
+    }
+}
@@ -1,22 +1,67 @@
-use crate::utils::read_lines;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::parse_lines;
 
-pub fn part_1() -> u32 {
-    read_lines(3)
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 3;
+    const TITLE: &'static str = "Rucksack Reorganization";
+
+    type Parsed = Vec<String>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(rucksacks: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(rucksacks).map(Answer::from)
+    }
+
+    fn part_2(rucksacks: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(rucksacks).map(Answer::from)
+    }
+}
+
+pub fn part_1(rucksacks: &[String]) -> Result<u32, SolveError> {
+    Ok(rucksacks
         .iter()
         .map(|line| priority(&find_common_item(&split_string(line))))
-        .sum()
+        .sum())
 }
 
-pub fn part_2() -> u32 {
-    let lines = read_lines(3);
-
-    lines
+pub fn part_2(rucksacks: &[String]) -> Result<u32, SolveError> {
+    Ok(rucksacks
         .chunks(3)
         .map(|chunk| priority(&find_common_item(chunk)))
-        .sum()
+        .sum())
+}
+
+pub fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    parse_lines(input, parse_line)
 }
 
-fn split_string(line: &String) -> Vec<String> {
+fn parse_line(line: &str) -> Result<String, ParseError> {
+    if let Some((index, char)) = line
+        .char_indices()
+        .find(|(_, char)| !char.is_ascii_alphabetic())
+    {
+        return Err(ParseError::new(format!("expected an item letter, got '{char}'"))
+            .at(line, &line[index..]));
+    }
+
+    if !line.len().is_multiple_of(2) {
+        return Err(ParseError::new(format!(
+            "expected an even number of items, got {}",
+            line.len()
+        )));
+    }
+
+    Ok(line.to_owned())
+}
+
+fn split_string(line: &str) -> Vec<String> {
     let length = line.len();
     vec![line[..length / 2].to_owned(), line[length / 2..].to_owned()]
 }
@@ -30,12 +75,17 @@ fn find_common_item(chunk: &[String]) -> Vec<char> {
 
     chars
         .into_iter()
-        .filter(|char| chunk[0].chars().find(|inner_char| inner_char == char).is_some())
+        .filter(|char| {
+            chunk[0]
+                .chars()
+                .find(|inner_char| inner_char == char)
+                .is_some()
+        })
         .collect()
 }
 
-fn priority(char: &Vec<char>) -> u32 {
-    if let Some(char) = char.get(0) {
+fn priority(char: &[char]) -> u32 {
+    if let Some(char) = char.first() {
         match char {
             'a'..='z' => *char as u32 - 'a' as u32 + 1,
             'A'..='Z' => *char as u32 - 'A' as u32 + 27,
@@ -45,3 +95,26 @@ fn priority(char: &Vec<char>) -> u32 {
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(157));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(70));
+    }
+}
@@ -1,62 +1,109 @@
-use regex::Regex;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::interval::IntervalSet;
+use crate::utils::parse::{complete, Cursor};
+use crate::utils::parse_lines;
 
-use crate::utils::read_lines;
+pub struct Day;
 
-pub fn part_1() -> usize {
-    read_lines(4)
-        .iter()
-        .map(parse_line)
-        .filter(|ranges| fully_covered(&ranges))
-        .count()
+impl Puzzle for Day {
+    const DAY: usize = 4;
+    const TITLE: &'static str = "Camp Cleanup";
+
+    type Parsed = Vec<Pair>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(pairs: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(pairs).map(Answer::from)
+    }
+
+    fn part_2(pairs: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(pairs).map(Answer::from)
+    }
 }
 
-pub fn part_2() -> usize {
-    read_lines(4)
-        .iter()
-        .map(parse_line)
-        .filter(|ranges| overlapped(&ranges))
-        .count()
+pub fn part_1(pairs: &[Pair]) -> Result<usize, SolveError> {
+    Ok(pairs.iter().filter(|ranges| fully_covered(ranges)).count())
 }
 
-struct Range {
-    from: u32,
-    to: u32,
+pub fn part_2(pairs: &[Pair]) -> Result<usize, SolveError> {
+    Ok(pairs.iter().filter(|ranges| overlapped(ranges)).count())
 }
 
-impl Range {
-    pub fn covered_by(&self, other: &Range) -> bool {
-        other.from <= self.from && other.to >= self.to
-    }
+pub fn parse(input: &str) -> Result<Vec<Pair>, ParseError> {
+    parse_lines(input, parse_line)
+}
 
-    pub fn overlap_by(&self, other: &Range) -> bool {
-        Self::in_range(self.from, other) || Self::in_range(self.to, other)
-    }
+pub type Pair = (Sections, Sections);
 
-    fn in_range(num: u32, range: &Range) -> bool {
-        num >= range.from && num <= range.to
-    }
+pub type Sections = IntervalSet<u32>;
+
+fn parse_line(line: &str) -> Result<Pair, ParseError> {
+    complete(line, |cursor| {
+        Ok((parse_sections(cursor)?, cursor.field(",", parse_sections)?))
+    })
 }
 
-fn parse_line(line: &String) -> (Range, Range) {
-    let temlate = Regex::new(r"^(\d+)-(\d+),(\d+)-(\d+)$").unwrap();
-    let captures = temlate.captures(line).unwrap();
+fn parse_sections(cursor: &mut Cursor) -> Result<Sections, ParseError> {
+    let start = cursor.rest();
+    let from = cursor.unsigned::<u32>()?;
+    let to = cursor.field("-", Cursor::unsigned::<u32>)?;
 
-    (
-        Range {
-            from: captures[1].parse::<u32>().unwrap(),
-            to: captures[2].parse::<u32>().unwrap(),
-        },
-        Range {
-            from: captures[3].parse::<u32>().unwrap(),
-            to: captures[4].parse::<u32>().unwrap(),
-        },
-    )
+    if from > to {
+        return Err(cursor.error_at(start, format!("the sections {from}-{to} are reversed")));
+    }
+
+    // The assignments are inclusive on both ends
+    let end = to.checked_add(1).ok_or_else(|| {
+        cursor.error_at(start, format!("sections are numbered up to {}", u32::MAX - 1))
+    })?;
+
+    Ok(Sections::from(from..end))
 }
 
-fn fully_covered(ranges: &(Range, Range)) -> bool {
-    ranges.0.covered_by(&ranges.1) || ranges.1.covered_by(&ranges.0)
+fn fully_covered((first, second): &Pair) -> bool {
+    first.difference(second).is_empty() || second.difference(first).is_empty()
 }
 
-fn overlapped(ranges: &(Range, Range)) -> bool {
-    ranges.0.overlap_by(&ranges.1) || ranges.1.overlap_by(&ranges.0)
+fn overlapped((first, second): &Pair) -> bool {
+    !first.intersection(second).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(2));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(4));
+    }
+
+    #[test]
+    fn rejects_sections_out_of_order_or_range() {
+        assert_eq!(
+            parse("2-4,6-8\n5-3,1-2").unwrap_err().to_string(),
+            "line 2, column 1: the sections 5-3 are reversed\n   |\n 2 | 5-3,1-2\n   | ^"
+        );
+        assert_eq!(
+            parse("1-4294967295,2-3").unwrap_err().to_string(),
+            "line 1, column 1: sections are numbered up to 4294967294\n   |\n 1 | 1-4294967295,2-3\n   | ^"
+        );
+    }
 }
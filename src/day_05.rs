@@ -1,57 +1,168 @@
 use std::str::Chars;
 
 use itertools::{Chunk, Itertools};
-use regex::Regex;
 
-use crate::utils::{read_input_to_string, string_to_lines};
+use crate::error::{ParseError, SolveError};
+use crate::simulation::Simulation;
+use crate::solution::{Answer, Puzzle};
+use crate::utils::parse::{complete, Cursor};
+use crate::utils::{records, Record};
 
-pub fn part_1() -> String {
-    process(false)
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 5;
+    const TITLE: &'static str = "Supply Stacks";
+
+    type Parsed = Supplies;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(supplies: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(supplies).map(Answer::from)
+    }
+
+    fn part_2(supplies: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(supplies).map(Answer::from)
+    }
 }
 
-pub fn part_2() -> String {
-    process(true)
+pub fn part_1(supplies: &Supplies) -> Result<String, SolveError> {
+    Ok(process(supplies, 1))
 }
 
-fn process(all_at_once: bool) -> String {
-    let (stacks, instructions) = split_into_parts(read_input_to_string(5));
-    let mut stacks = parse_stacks(stacks);
+pub fn part_2(supplies: &Supplies) -> Result<String, SolveError> {
+    Ok(process(supplies, 2))
+}
 
-    for instruction in instructions.iter().map(parse_instruction) {
-        stacks = move_crates(stacks, instruction, all_at_once);
+/// The crane of `part` following the procedure one instruction at a time.
+pub fn simulation(supplies: &Supplies, part: usize) -> Crane<'_> {
+    Crane {
+        stacks: supplies.stacks.clone(),
+        instructions: &supplies.instructions,
+        next: 0,
+        all_at_once: part == 2,
     }
+}
+
+pub struct Supplies {
+    stacks: Vec<Vec<char>>,
+    instructions: Vec<Instruction>,
+}
 
-    stacks
+pub fn parse(input: &str) -> Result<Supplies, ParseError> {
+    let records = records(input);
+    let (drawing, procedure) = match records.split_first() {
+        Some((drawing, procedure)) if !procedure.is_empty() => (drawing, procedure),
+        _ => {
+            return Err(ParseError::new(
+                "expected a blank line between the stacks and the instructions",
+            ))
+        }
+    };
+
+    let stacks = parse_stacks(drawing)?;
+    let mut heights = stacks.iter().map(Vec::len).collect_vec();
+    let mut instructions = vec![];
+
+    for record in procedure {
+        instructions.extend(record.parse_lines(|line| {
+            let instruction = parse_instruction(line, stacks.len())?;
+
+            // Both cranes move the same number of crates, so the heights can be checked upfront
+            if heights[instruction.from - 1] < instruction.quantity {
+                return Err(ParseError::new(format!(
+                    "can't move {} crates from stack {}, it only has {}",
+                    instruction.quantity,
+                    instruction.from,
+                    heights[instruction.from - 1]
+                )));
+            }
+
+            heights[instruction.from - 1] -= instruction.quantity;
+            heights[instruction.to - 1] += instruction.quantity;
+
+            Ok(instruction)
+        })?);
+    }
+
+    Ok(Supplies {
+        stacks,
+        instructions,
+    })
+}
+
+fn process(supplies: &Supplies, part: usize) -> String {
+    let mut crane = simulation(supplies, part);
+    crane.run();
+
+    crane
+        .stacks
         .iter()
         .map(|stack| stack.last().unwrap_or(&'_').to_owned())
         .collect()
 }
 
-fn split_into_parts(input: String) -> (Vec<String>, Vec<String>) {
-    let parts = input.split("\n\n").collect_vec();
-    assert_eq!(parts.len(), 2);
+pub struct Crane<'a> {
+    stacks: Vec<Vec<char>>,
+    instructions: &'a [Instruction],
+    next: usize,
+    all_at_once: bool,
+}
+
+impl Simulation for Crane<'_> {
+    /// The stacks from bottom to top.
+    type State = Vec<Vec<char>>;
+
+    fn step(&mut self) {
+        move_crates(
+            &mut self.stacks,
+            &self.instructions[self.next],
+            self.all_at_once,
+        );
+        self.next += 1;
+    }
+
+    fn is_done(&self) -> bool {
+        self.next == self.instructions.len()
+    }
 
-    (string_to_lines(parts[0]), string_to_lines(parts[1]))
+    fn state(&self) -> Vec<Vec<char>> {
+        self.stacks.clone()
+    }
 }
 
-fn parse_stacks(mut lines: Vec<String>) -> Vec<Vec<char>> {
-    let num_of_stacks = number_of_stacks(lines.pop().unwrap());
-    let mut stacks = vec![vec![]; num_of_stacks];
+fn parse_stacks(drawing: &Record) -> Result<Vec<Vec<char>>, ParseError> {
+    let Some((numbers, crates)) = drawing.lines.split_last() else {
+        return Err(ParseError::new("expected a drawing of the stacks"));
+    };
 
-    lines.reverse();
+    let num_of_stacks = number_of_stacks(numbers);
+    let mut stacks = vec![vec![]; num_of_stacks];
 
-    for line in lines {
+    for (line_index, line) in crates.iter().enumerate().rev() {
         for (index, chunk) in line.chars().chunks(4).into_iter().enumerate() {
             if let Some(char) = stack_value(chunk) {
+                if index >= num_of_stacks {
+                    return Err(ParseError::new(format!(
+                        "crate '{char}' is outside of the {num_of_stacks} numbered stacks"
+                    ))
+                    .at_column(index * 4 + 2)
+                    .in_line(drawing.first_line + line_index, line));
+                }
+
                 stacks[index].push(char);
             }
         }
     }
 
-    stacks
+    Ok(stacks)
 }
 
-fn number_of_stacks(line: String) -> usize {
+fn number_of_stacks(line: &str) -> usize {
     line.chars().chunks(4).into_iter().collect_vec().len()
 }
 
@@ -67,22 +178,31 @@ struct Instruction {
     to: usize,
 }
 
-fn parse_instruction(line: &String) -> Instruction {
-    let template = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
-    let captures = template.captures(line).unwrap();
+fn parse_instruction(line: &str, num_of_stacks: usize) -> Result<Instruction, ParseError> {
+    let stack = |cursor: &mut Cursor| {
+        let part = cursor.rest();
+        let stack = cursor.unsigned::<usize>()?;
 
-    Instruction {
-        quantity: captures[1].parse::<usize>().unwrap(),
-        from: captures[2].parse::<usize>().unwrap(),
-        to: captures[3].parse::<usize>().unwrap(),
-    }
+        if stack == 0 || stack > num_of_stacks {
+            return Err(cursor.error_at(
+                part,
+                format!("expected a stack between 1 and {num_of_stacks}, got {stack}"),
+            ));
+        }
+
+        Ok(stack)
+    };
+
+    complete(line, |cursor| {
+        Ok(Instruction {
+            quantity: cursor.field("move ", Cursor::unsigned)?,
+            from: cursor.field(" from ", stack)?,
+            to: cursor.field(" to ", stack)?,
+        })
+    })
 }
 
-fn move_crates(
-    mut stacks: Vec<Vec<char>>,
-    instruction: Instruction,
-    all_at_once: bool,
-) -> Vec<Vec<char>> {
+fn move_crates(stacks: &mut [Vec<char>], instruction: &Instruction, all_at_once: bool) {
     let mut tmp = (0..instruction.quantity)
         .map(|_| stacks[instruction.from - 1].pop().unwrap())
         .collect_vec();
@@ -92,6 +212,39 @@ fn move_crates(
     }
 
     stacks[instruction.to - 1].extend(tmp);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    stacks
+    const EXAMPLE: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok("CMZ".to_owned()));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok("MCD".to_owned()));
+    }
+
+    #[test]
+    fn snapshots_every_instruction() {
+        let supplies = parse(EXAMPLE).unwrap();
+        let snapshots = simulation(&supplies, 1).snapshots().collect_vec();
+
+        assert_eq!(snapshots.len(), 5);
+        assert_eq!(snapshots[0], [vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]);
+        assert_eq!(snapshots[2], [vec![], vec!['M', 'C'], vec!['P', 'D', 'N', 'Z']]);
+    }
 }
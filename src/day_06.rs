@@ -1,20 +1,45 @@
-use crate::utils::read_input_to_string;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
 
-pub fn part_1() -> usize {
-    let input = read_input_to_string(6);
-    find_start(&input, 4)
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 6;
+    const TITLE: &'static str = "Tuning Trouble";
+
+    type Parsed = String;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        Ok(input.trim().to_owned())
+    }
+
+    fn part_1(signal: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(signal).map(Answer::from)
+    }
+
+    fn part_2(signal: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(signal).map(Answer::from)
+    }
+}
+
+pub fn part_1(signal: &str) -> Result<usize, SolveError> {
+    find_start(signal, 4)
 }
 
-pub fn part_2() -> usize {
-    let input = read_input_to_string(6);
-    find_start(&input, 14)
+pub fn part_2(signal: &str) -> Result<usize, SolveError> {
+    find_start(signal, 14)
 }
 
-fn find_start(line: &str, uniques: usize) -> usize {
-    (0..line.len() - uniques)
+fn find_start(line: &str, uniques: usize) -> Result<usize, SolveError> {
+    let no_marker =
+        || SolveError::no_answer(format!("no {uniques} different characters follow each other"));
+    let last = line.len().checked_sub(uniques).ok_or_else(no_marker)?;
+
+    (0..=last)
         .find(|index| !contains_duplicates(&line[*index..*index + uniques]))
         .map(|index| index + uniques)
-        .unwrap_or(0)
+        .ok_or_else(no_marker)
 }
 
 fn contains_duplicates(line: &str) -> bool {
@@ -22,3 +47,42 @@ fn contains_duplicates(line: &str) -> bool {
         .find(|(index, char)| line[index + 1..].find(*char).is_some())
         .is_some()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_1_examples() {
+        assert_eq!(part_1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Ok(7));
+        assert_eq!(part_1("bvwbjplbgvbhsrlpgdmjqwftvncz"), Ok(5));
+        assert_eq!(part_1("nppdvjthqldpwncqszvftbrmjlhg"), Ok(6));
+        assert_eq!(part_1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), Ok(10));
+        assert_eq!(part_1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), Ok(11));
+    }
+
+    #[test]
+    fn finds_the_marker_at_the_very_end() {
+        assert_eq!(part_1("abcd"), Ok(4));
+        assert_eq!(part_1("aabcd"), Ok(5));
+    }
+
+    #[test]
+    fn reports_a_missing_marker() {
+        let missing = Err(SolveError::no_answer(
+            "no 4 different characters follow each other",
+        ));
+
+        assert_eq!(part_1("abc"), missing);
+        assert_eq!(part_1("abcabcabc"), missing);
+    }
+
+    #[test]
+    fn part_2_examples() {
+        assert_eq!(part_2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Ok(19));
+        assert_eq!(part_2("bvwbjplbgvbhsrlpgdmjqwftvncz"), Ok(23));
+        assert_eq!(part_2("nppdvjthqldpwncqszvftbrmjlhg"), Ok(23));
+        assert_eq!(part_2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), Ok(29));
+        assert_eq!(part_2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), Ok(26));
+    }
+}
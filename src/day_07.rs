@@ -1,45 +1,77 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use regex::Regex;
 
-use crate::utils::read_lines;
+use crate::error::{ParseError, SolveError};
+use crate::params::params;
+use crate::solution::{Answer, Puzzle};
+use crate::trace::warn;
+use crate::utils::parse::complete;
+use crate::utils::parse_lines;
 
-pub fn part_1() -> u32 {
-    let fs = prepare_fs();
+pub struct Day;
 
-    fs.dirs
+params! {
+    pub struct Params {
+        pub disk_size: u32 = 70_000_000,
+        pub needed_space: u32 = 30_000_000,
+    }
+}
+
+impl Puzzle for Day {
+    const DAY: usize = 7;
+    const TITLE: &'static str = "No Space Left On Device";
+
+    type Parsed = Fs;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(fs: &Self::Parsed, _: &Params) -> Result<Answer, SolveError> {
+        part_1(fs).map(Answer::from)
+    }
+
+    fn part_2(fs: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_2(fs, params).map(Answer::from)
+    }
+}
+
+pub fn part_1(fs: &Fs) -> Result<u32, SolveError> {
+    Ok(fs
+        .dirs
         .iter()
-        .map(|dir| dir.size(&fs))
+        .map(|dir| dir.size(fs))
         .filter(|size| *size <= 100000)
-        .sum()
+        .sum())
 }
 
-pub fn part_2() -> u32 {
-    let disk_space = 70000000;
-    let need_to_have_space = 30000000;
-    let fs = prepare_fs();
-
-    let used_space = fs.dirs[0].size(&fs);
-    assert!(disk_space >= used_space);
+pub fn part_2(fs: &Fs, params: &Params) -> Result<u32, SolveError> {
+    let used_space = fs.dirs[0].size(fs);
+    let free_space = params.disk_size.checked_sub(used_space).ok_or_else(|| {
+        SolveError::no_answer(format!(
+            "{used_space} used exceeds the disk size of {}",
+            params.disk_size
+        ))
+    })?;
 
-    let need_to_free_up_space = need_to_have_space - (disk_space - used_space);
+    let need_to_free_up_space = params.needed_space.saturating_sub(free_space);
 
-    let mut all_sizes = fs.dirs.iter().map(|dir| dir.size(&fs)).collect_vec();
+    let mut all_sizes = fs.dirs.iter().map(|dir| dir.size(fs)).collect_vec();
 
     all_sizes.sort();
 
-    all_sizes
+    Ok(all_sizes
         .into_iter()
         .find(|size| *size >= need_to_free_up_space)
-        .unwrap_or(0)
+        .unwrap_or(0))
 }
 
-fn prepare_fs() -> Fs {
-    read_lines(7)
-        .iter()
-        .map(parse_line)
-        .fold(Fs::new(), |fs, line| fs.process_line(line))
+pub fn parse(input: &str) -> Result<Fs, ParseError> {
+    Ok(parse_lines(input, parse_line)?
+        .into_iter()
+        .fold(Fs::new(), |fs, line| fs.process_line(line)))
 }
 
 #[derive(Debug)]
@@ -60,54 +92,60 @@ enum OutputLine {
     Dir(String),
 }
 
-fn parse_line(line: &String) -> Line {
+fn parse_line(line: &str) -> Result<Line, ParseError> {
     use Line::*;
 
-    match &line[0..2] {
-        "$ " => Cmd(parse_command(&line[2..])),
-        _ => Output(parse_output(&line)),
+    match line.strip_prefix("$ ") {
+        Some(command) => Ok(Cmd(parse_command(line, command)?)),
+        None => Ok(Output(parse_output(line)?)),
     }
 }
 
-fn parse_command(line: &str) -> Command {
+fn parse_command(line: &str, command: &str) -> Result<Command, ParseError> {
     use Command::*;
 
-    match &line[0..2] {
-        "cd" => Cd(line[3..].to_owned()),
-        "ls" => Ls,
-        _ => unreachable!(),
+    if let Some(path) = command.strip_prefix("cd ") {
+        return Ok(Cd(path.to_owned()));
+    }
+
+    match command {
+        "ls" => Ok(Ls),
+        _ => Err(ParseError::new(format!("unknown command '{command}'")).at(line, command)),
     }
 }
 
-fn parse_output(line: &str) -> OutputLine {
-    if line.starts_with("dir ") {
-        return OutputLine::Dir(line[4..].to_owned());
+fn parse_output(line: &str) -> Result<OutputLine, ParseError> {
+    if let Some(name) = line.strip_prefix("dir ") {
+        return Ok(OutputLine::Dir(name.to_owned()));
     }
 
-    let template = Regex::new(r"^(\d+) (.+)$").unwrap();
-    let captures = template.captures(line).unwrap();
+    complete(line, |cursor| {
+        let size = cursor.unsigned()?;
+        cursor.tag(" ")?;
 
-    OutputLine::File(File {
-        name: captures[2].to_owned(),
-        size: captures[1].parse::<u32>().unwrap(),
+        if cursor.take_while(|_| true).is_empty() {
+            return Err(cursor.error("expected a file name"));
+        }
+
+        Ok(OutputLine::File(File { size }))
     })
 }
 
 #[derive(Debug)]
-struct Fs {
+pub struct Fs {
     dirs: Vec<Dir>,
     indexed_path: Vec<usize>,
 }
 
 impl Fs {
-    pub fn new() -> Self {
+    fn new() -> Self {
         Self {
-            dirs: vec![Dir::root()],
+            dirs: vec![Dir::default()],
             indexed_path: vec![0],
         }
     }
 
-    pub fn process_line(mut self, line: Line) -> Self {
+    fn process_line(mut self, line: Line) -> Self {
         match line {
             Line::Cmd(cmd) => self.process_cmd(cmd),
             Line::Output(output) => self.process_output(output),
@@ -119,7 +157,7 @@ impl Fs {
     fn process_cmd(&mut self, cmd: Command) {
         match cmd {
             Command::Cd(path) => self.process_cd_cmd(&path),
-            Command::Ls => return,
+            Command::Ls => (),
         }
     }
 
@@ -135,7 +173,7 @@ impl Fs {
                 let current_dir_index = self.indexed_path.last().unwrap();
 
                 match self.dirs[*current_dir_index].dirs.get(path) {
-                    None => println!("There's no such a nested dir: {path}"),
+                    None => warn!("cd into unknown directory {path}, staying where we are"),
                     Some(dir_index) => {
                         self.indexed_path.push(*dir_index);
                     }
@@ -149,11 +187,7 @@ impl Fs {
             OutputLine::Dir(dir_name) => {
                 let current_dir_index = self.indexed_path.last().unwrap();
 
-                self.dirs.push(Dir {
-                    name: dir_name.to_owned(),
-                    parent_dir_index: Some(*current_dir_index),
-                    ..Default::default()
-                });
+                self.dirs.push(Dir::default());
 
                 let dir_index = self.dirs.len() - 1;
 
@@ -172,20 +206,11 @@ impl Fs {
 
 #[derive(Debug, Default)]
 struct Dir {
-    name: String,
-    parent_dir_index: Option<usize>,
     files: Vec<File>,
     dirs: HashMap<String, usize>,
 }
 
 impl Dir {
-    pub fn root() -> Self {
-        Self {
-            name: "/".to_owned(),
-            ..Default::default()
-        }
-    }
-
     pub fn size(&self, fs: &Fs) -> u32 {
         let files_sizes: u32 = self.files.iter().map(|file| file.size).sum();
         let dirs_sizes: u32 = self
@@ -200,6 +225,45 @@ impl Dir {
 
 #[derive(Debug)]
 struct File {
-    name: String,
     size: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(95437));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(24933642));
+    }
+}
@@ -1,112 +1,124 @@
-use itertools::Itertools;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::grid::{Grid, Pos};
+use crate::utils::image::{Image, Rgb};
+use crate::utils::point::Direction;
 
-use crate::utils::read_lines;
+pub struct Day;
 
-pub fn part_1() -> usize {
-    let data = Data::new(read_lines(8).iter().map(parse_line).collect_vec());
+impl Puzzle for Day {
+    const DAY: usize = 8;
+    const TITLE: &'static str = "Treetop Tree House";
 
-    data.visibility_map()
-        .iter()
-        .map(|column| column.iter().filter(|cell| **cell).count())
-        .sum()
+    type Parsed = Grid<u32>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(trees: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(trees).map(Answer::from)
+    }
+
+    fn part_2(trees: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(trees).map(Answer::from)
+    }
+
+    fn render(trees: &Self::Parsed, _: &()) -> Result<Option<Image>, SolveError> {
+        Ok(Some(render(trees)))
+    }
 }
 
-pub fn part_2() -> usize {
-    let data = Data::new(read_lines(8).iter().map(parse_line).collect_vec());
+pub fn part_1(trees: &Grid<u32>) -> Result<usize, SolveError> {
+    Ok(trees
+        .positions()
+        .filter(|pos| is_visible(trees, *pos))
+        .count())
+}
 
-    *(data
-        .scenic_scores()
-        .iter()
-        .map(|column| column.iter().max().unwrap())
+pub fn part_2(trees: &Grid<u32>) -> Result<usize, SolveError> {
+    Ok(trees
+        .positions()
+        .map(|pos| {
+            Direction::ALL
+                .iter()
+                .map(|direction| viewing_distance(trees, pos, *direction))
+                .product()
+        })
         .max()
-        .unwrap())
+        .unwrap_or(0))
 }
 
-fn parse_line(line: &String) -> Vec<u32> {
-    line.chars()
-        .map(|char| char.to_digit(10).unwrap())
-        .collect_vec()
-}
+/// The visible trees in green and the hidden ones in grey, lighter the taller they are.
+pub fn render(trees: &Grid<u32>) -> Image {
+    Image::from_grid(trees, |pos, height| {
+        let (short, tall) = if is_visible(trees, pos) {
+            (Rgb(16, 48, 16), Rgb(128, 224, 64))
+        } else {
+            (Rgb(24, 24, 24), Rgb(112, 112, 112))
+        };
 
-struct Coord {
-    x: usize,
-    y: usize,
+        short.mix(tall, *height as f64 / 9.0)
+    })
+    .with_scale(8)
 }
 
-#[derive(Debug)]
-struct Data {
-    data: Vec<Vec<u32>>,
-    x_len: usize,
-    y_len: usize,
+pub fn parse(input: &str) -> Result<Grid<u32>, ParseError> {
+    Grid::parse(input, |char| char.to_digit(10))
 }
 
-impl Data {
-    fn new(data: Vec<Vec<u32>>) -> Self {
-        let x_len = data[0].len();
-        let y_len = data.len();
-        Self { data, x_len, y_len }
-    }
+fn is_visible(trees: &Grid<u32>, pos: Pos) -> bool {
+    Direction::ALL.iter().any(|direction| {
+        trees
+            .ray(pos, *direction)
+            .all(|other| trees[other] < trees[pos])
+    })
+}
 
-    fn visibility_map(&self) -> Vec<Vec<bool>> {
-        self.iterate(true, |_, x1, x2, y1, y2| {
-            x1.is_none() || x2.is_none() || y1.is_none() || y2.is_none()
-        })
-    }
+fn viewing_distance(trees: &Grid<u32>, pos: Pos, direction: Direction) -> usize {
+    let mut distance = 0;
 
-    fn scenic_scores(&self) -> Vec<Vec<usize>> {
-        self.iterate(0, |coord, x1, x2, y1, y2| {
-            let x1 = coord.x - x1.unwrap_or(0);
-            let x2 = x2.unwrap_or(self.x_len - 1) - coord.x;
-            let y1 = coord.y - y1.unwrap_or(0);
-            let y2 = y2.unwrap_or(self.y_len - 1) - coord.y;
+    for other in trees.ray(pos, direction) {
+        distance += 1;
 
-            x1 * x2 * y1 * y2
-        })
+        if trees[other] >= trees[pos] {
+            break;
+        }
     }
 
-    fn iterate<F, T>(&self, init: T, closure: F) -> Vec<Vec<T>>
-    where
-        T: Default + Clone,
-        F: Fn(&Coord, Option<usize>, Option<usize>, Option<usize>, Option<usize>) -> T,
-    {
-        let mut output = vec![vec![init; self.x_len]; self.y_len];
+    distance
+}
 
-        for y in 0..self.y_len {
-            for x in 0..self.x_len {
-                let coord = Coord { x, y };
+#[cfg(test)]
+mod tests {
+    use crate::utils::point::Point;
 
-                let x1 = self.with_x_range(&coord, x, 0);
-                let x2 = self.with_x_range(&coord, x + 1, self.x_len);
-                let y1 = self.with_y_range(&coord, y, 0);
-                let y2 = self.with_y_range(&coord, y + 1, self.y_len);
+    use super::*;
 
-                output[y][x] = closure(&coord, x1, x2, y1, y2);
-            }
-        }
+    const EXAMPLE: &str = "\
+30373
+25512
+65332
+33549
+35390";
 
-        output
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(21));
     }
 
-    fn with_x_range(&self, coord: &Coord, from: usize, to: usize) -> Option<usize> {
-        let closure = |x: &usize| self.data[coord.y][*x] >= self.data[coord.y][coord.x];
-
-        self.find(from, to, closure)
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(8));
     }
 
-    fn with_y_range(&self, coord: &Coord, from: usize, to: usize) -> Option<usize> {
-        let closure = |y: &usize| self.data[*y][coord.x] >= self.data[coord.y][coord.x];
-
-        self.find(from, to, closure)
-    }
+    #[test]
+    fn renders_hidden_trees_in_grey() {
+        let image = render(&parse(EXAMPLE).unwrap());
 
-    fn find<F>(&self, from: usize, to: usize, closure: F) -> Option<usize>
-    where
-        F: Fn(&usize) -> bool,
-    {
-        if from < to {
-            (from..to).find(closure)
-        } else {
-            (to..from).rev().find(closure)
-        }
+        assert_eq!((image.width(), image.height()), (40, 40));
+        assert_eq!(image.get(Point::new(0, 0)), Some(Rgb(53, 107, 32)));
+        assert_eq!(image.get(Point::new(3, 1)), Some(Rgb(34, 34, 34)));
     }
 }
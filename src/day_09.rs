@@ -1,210 +1,295 @@
-use std::collections::{HashMap, HashSet};
-
-use crate::utils::read_lines;
-
-pub fn part_1() -> usize {
-    calculate(2, false).visited.len()
+use std::{collections::HashSet, iter};
+
+use crate::animation::{Animation, Frame};
+use crate::error::{ParseError, SolveError};
+use crate::params::params;
+use crate::simulation::Simulation;
+use crate::solution::{Answer, Puzzle};
+use crate::utils::grid::SparseGrid;
+use crate::utils::image::{Image, Rgb};
+use crate::utils::point::{Direction, Point};
+use crate::utils::parse::{complete, Cursor};
+use crate::utils::parse_lines;
+
+params! {
+    pub struct Params {
+        pub part_1_knots: usize = 2,
+        pub part_2_knots: usize = 10,
+    }
 }
 
-pub fn part_2() -> usize {
-    calculate(10, false).visited.len()
-}
+pub struct Day;
 
-fn calculate(length: usize, visualize_output: bool) -> Rope {
-    let mut rope = Rope::new(length);
+impl Puzzle for Day {
+    const DAY: usize = 9;
+    const TITLE: &'static str = "Rope Bridge";
 
-    for (direction, amount) in read_lines(9).iter().map(parse_line) {
-        rope.process(&direction, amount, visualize_output);
+    type Parsed = Vec<Motion>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
     }
 
-    rope
-}
+    fn part_1(motions: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_1(motions, params).map(Answer::from)
+    }
 
-#[derive(Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+    fn part_2(motions: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_2(motions, params).map(Answer::from)
+    }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Coord {
-    x: i32,
-    y: i32,
-}
+    fn render(motions: &Self::Parsed, params: &Params) -> Result<Option<Image>, SolveError> {
+        render(motions, params).map(Some)
+    }
 
-impl Coord {
-    fn diff(&self, other: &Coord) -> Coord {
-        Coord {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
+    fn animate<'a>(
+        motions: &'a Self::Parsed,
+        params: &Params,
+        part: usize,
+    ) -> Result<Option<Animation<'a>>, SolveError> {
+        Ok(Some(Box::new(simulation(motions, params, part)?.map_state(frame))))
     }
 }
 
-struct Rope {
-    rope: Vec<Coord>,
-    visited: HashSet<Coord>,
+pub fn part_1(motions: &[Motion], params: &Params) -> Result<usize, SolveError> {
+    Ok(calculate(motions, params.part_1_knots)?.visited.len())
 }
 
-impl Rope {
-    fn new(length: usize) -> Self {
-        let init_coord = Coord { x: 0, y: 0 };
+pub fn part_2(motions: &[Motion], params: &Params) -> Result<usize, SolveError> {
+    Ok(calculate(motions, params.part_2_knots)?.visited.len())
+}
 
-        let mut rope = vec![];
+/// The rope of `part` moving one step of the head at a time.
+pub fn simulation(motions: &[Motion], params: &Params, part: usize) -> Result<Walk, SolveError> {
+    let knots = match part {
+        1 => params.part_1_knots,
+        _ => params.part_2_knots,
+    };
 
-        for _ in 0..length {
-            rope.push(init_coord.clone());
-        }
+    Walk::new(motions, knots)
+}
 
-        let mut visited = HashSet::new();
-        visited.insert(init_coord.clone());
+/// The trail of the part 1 tail in blue under the part 2 one in orange, with the start in red.
+pub fn render(motions: &[Motion], params: &Params) -> Result<Image, SolveError> {
+    let mut trails = SparseGrid::new();
 
-        Self { rope, visited }
+    for (knots, color) in [
+        (params.part_1_knots, Rgb(64, 128, 224)),
+        (params.part_2_knots, Rgb(240, 144, 32)),
+    ] {
+        for pos in calculate(motions, knots)?.visited {
+            trails.insert(pos, color);
+        }
     }
 
-    fn process(&mut self, direction: &Direction, amount: u32, visualize_output: bool) {
-        use Direction::*;
+    trails.insert(Point::default(), Rgb(224, 32, 32));
 
-        if amount == 0 {
-            return;
-        }
+    Ok(Image::from_sparse(&trails, Rgb(16, 16, 16), |_, color| *color).with_scale(4))
+}
 
-        match direction {
-            Up => self.rope[0].y += 1,
-            Down => self.rope[0].y -= 1,
-            Left => self.rope[0].x -= 1,
-            Right => self.rope[0].x += 1,
-        }
+pub fn parse(input: &str) -> Result<Vec<Motion>, ParseError> {
+    parse_lines(input, parse_line)
+}
 
-        if visualize_output {
-            visualize(&self, &format!("====== {amount} {:?} ======", direction));
-        }
+/// A direction to move the head in and the number of steps.
+pub type Motion = (Direction, u32);
 
-        self.process_rest(1, visualize_output);
+fn calculate(motions: &[Motion], length: usize) -> Result<Rope, SolveError> {
+    let mut walk = Walk::new(motions, length)?;
+    walk.run();
 
-        let tail_index = self.rope.len() - 1;
-        self.visited.insert(self.rope[tail_index].clone());
+    Ok(walk.rope)
+}
 
-        self.process(&direction, amount - 1, visualize_output);
-    }
+#[derive(Clone, Debug)]
+pub struct Rope {
+    /// From the head to the tail.
+    pub knots: Vec<Point<isize>>,
+    /// Every position the tail has been at.
+    pub visited: HashSet<Point<isize>>,
+}
 
-    fn process_rest(&mut self, index: usize, visualize_output: bool) {
-        if index == 0 || index >= self.rope.len() {
-            return;
+impl Rope {
+    fn new(length: usize) -> Result<Self, SolveError> {
+        if length == 0 {
+            return Err(SolveError::no_answer("the rope needs at least one knot"));
         }
 
-        let prev_index = index - 1;
-        let diff = self.rope[prev_index].diff(&self.rope[index]);
-
-        let mut changed = false;
-
-        if diff.x.abs() > 1 && diff.y.abs() > 1 {
-            self.rope[index].x += diff.x.signum();
-            self.rope[index].y += diff.y.signum();
-            changed = true;
-        } else if diff.x.abs() > 1 {
-            self.rope[index].x += diff.x.signum();
-            self.rope[index].y = self.rope[prev_index].y;
-            changed = true;
-        } else if diff.y.abs() > 1 {
-            self.rope[index].x = self.rope[prev_index].x;
-            self.rope[index].y += diff.y.signum();
-            changed = true;
-        }
+        Ok(Self {
+            knots: vec![Point::default(); length],
+            visited: HashSet::from([Point::default()]),
+        })
+    }
+
+    /// Moves the head one step, then every knot after it that needs to follow.
+    fn step(&mut self, direction: Direction) {
+        self.knots[0] += direction.delta();
 
-        if changed {
-            self.process_rest(index + 1, visualize_output);
+        for index in 1..self.knots.len() {
+            let previous = self.knots[index - 1];
+            let knot = self.knots[index];
 
-            if visualize_output {
-                visualize(&self, &format!("  >> rest: {index}"));
+            // A knot that is no longer touching the previous one moves one step towards it
+            if previous.chebyshev(knot) <= 1 {
+                break;
             }
+
+            self.knots[index] += (previous - knot).signum();
         }
+
+        self.visited.insert(self.knots[self.knots.len() - 1]);
     }
 }
 
-fn parse_line(line: &String) -> (Direction, u32) {
-    use Direction::*;
-
-    let amount = line[2..].parse::<u32>().unwrap();
+/// The rope following the motions one step of the head at a time.
+pub struct Walk {
+    rope: Rope,
+    steps: Vec<Direction>,
+    next: usize,
+}
 
-    match &line[0..1] {
-        "U" => (Up, amount),
-        "D" => (Down, amount),
-        "L" => (Left, amount),
-        "R" => (Right, amount),
-        substr => unreachable!("Got '{substr}'"),
+impl Walk {
+    fn new(motions: &[Motion], length: usize) -> Result<Self, SolveError> {
+        let steps = motions
+            .iter()
+            .flat_map(|(direction, amount)| iter::repeat_n(*direction, *amount as usize))
+            .collect();
+
+        Ok(Self {
+            rope: Rope::new(length)?,
+            steps,
+            next: 0,
+        })
     }
 }
 
-fn visualize(rope: &Rope, title: &str) {
-    let mut min = Coord { x: 0, y: 0 };
-    let mut max = Coord { x: 0, y: 0 };
-    let mut rope_parts = HashMap::new();
+impl Simulation for Walk {
+    type State = Rope;
 
-    for (index, coord) in rope.rope.iter().enumerate() {
-        get_min_max(coord, &mut min, &mut max);
+    fn step(&mut self) {
+        self.rope.step(self.steps[self.next]);
+        self.next += 1;
+    }
 
-        if let None = rope_parts.get_mut(&(coord.x, coord.y)) {
-            rope_parts.insert((coord.x, coord.y), index);
-        }
+    fn is_done(&self) -> bool {
+        self.next == self.steps.len()
     }
 
-    for coord in &rope.visited {
-        get_min_max(coord, &mut min, &mut max);
+    fn state(&self) -> Rope {
+        self.rope.clone()
     }
+}
 
-    println!("{title}");
-    println!("");
+/// The knots over the tail's trail, `H` for the head and then their index.
+fn frame(walk: &Walk) -> Frame {
+    let rope = &walk.rope;
+    let mut cells = SparseGrid::new();
 
-    for y in (min.y..=max.y).rev() {
-        let mut line = String::new();
+    for pos in &rope.visited {
+        cells.insert(*pos, '#');
+    }
 
-        for x in min.x..=max.x {
-            match rope_parts.get(&(x, y)) {
-                None => {
-                    if x == 0 && y == 0 {
-                        line.push('s')
-                    } else {
-                        line.push('.')
-                    }
-                }
-                Some(index) => line.push(char::from_digit(*index as u32, 10).unwrap()),
-            }
-        }
+    cells.insert(Point::default(), 's');
 
-        line.extend("    ".chars());
-
-        for x in min.x..=max.x {
-            match &rope.visited.get(&Coord { x, y }) {
-                None => {
-                    if x == 0 && y == 0 {
-                        line.push('s')
-                    } else {
-                        line.push('.')
-                    }
-                }
-                Some(_) => line.push('#'),
-            }
-        }
+    for (index, knot) in rope.knots.iter().enumerate().rev() {
+        let char = match index {
+            0 => 'H',
+            _ => char::from_digit(index as u32, 10).unwrap_or('*'),
+        };
+
+        cells.insert(*knot, char);
+    }
 
-        println!("{line}");
-        println!("");
+    Frame {
+        caption: format!("visited {}", rope.visited.len()),
+        cells,
+        focus: rope.knots[0],
     }
 }
 
-fn get_min_max(coord: &Coord, min: &mut Coord, max: &mut Coord) {
-    if coord.x < min.x {
-        min.x = coord.x;
+fn parse_line(line: &str) -> Result<Motion, ParseError> {
+    complete(line, |cursor| {
+        let direction = match cursor.one_of(&["U", "D", "L", "R"])? {
+            "U" => Direction::Up,
+            "D" => Direction::Down,
+            "L" => Direction::Left,
+            _ => Direction::Right,
+        };
+
+        Ok((direction, cursor.field(" ", Cursor::unsigned)?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2";
+
+    const LARGER_EXAMPLE: &str = "\
+R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(13));
     }
-    if coord.x > max.x {
-        max.x = coord.x;
+
+    #[test]
+    fn part_2_examples() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(1));
+        assert_eq!(part_2(&parse(LARGER_EXAMPLE).unwrap(), &Params::default()), Ok(36));
     }
-    if coord.y < min.y {
-        min.y = coord.y;
+
+    #[test]
+    fn renders_both_trails() {
+        let image = render(&parse(EXAMPLE).unwrap(), &Params::default()).unwrap();
+
+        assert_eq!((image.width(), image.height()), (20, 20));
+        assert_eq!(image.get(Point::new(0, 4)), Some(Rgb(224, 32, 32)));
+        assert_eq!(image.get(Point::new(1, 4)), Some(Rgb(64, 128, 224)));
+        assert_eq!(image.get(Point::new(0, 0)), Some(Rgb(16, 16, 16)));
+    }
+
+    #[test]
+    fn snapshots_every_step_of_the_head() {
+        let motions = parse(EXAMPLE).unwrap();
+        let snapshots = simulation(&motions, &Params::default(), 1)
+            .unwrap()
+            .snapshots()
+            .collect::<Vec<_>>();
+
+        assert_eq!(snapshots.len(), 25);
+        assert_eq!(snapshots[4].knots, [Point::new(4, 0), Point::new(3, 0)]);
+        assert_eq!(snapshots[24].visited.len(), 13);
     }
-    if coord.y > max.y {
-        max.y = coord.y;
+
+    #[test]
+    fn animates_the_rope() {
+        let mut walk = Walk::new(&parse(EXAMPLE).unwrap(), 2).unwrap();
+        walk.run();
+
+        let frame = frame(&walk);
+
+        assert_eq!(frame.caption, "visited 13");
+        assert_eq!(
+            frame.crop(10, 10)[1..],
+            ["  ## ", "   ##", " 1H##", "    #", "s### "]
+        );
     }
 }
@@ -1,31 +1,95 @@
-use itertools::Itertools;
+use crate::animation::{Animation, Frame};
+use crate::error::{ParseError, SolveError};
+use crate::simulation::Simulation;
+use crate::solution::{Answer, Puzzle};
+use crate::utils::grid::{Grid, SparseGrid};
+use crate::utils::image::{Image, Rgb};
+use crate::utils::ocr;
+use crate::utils::parse::complete;
+use crate::utils::parse_lines;
+use crate::utils::point::Point;
 
-use crate::utils::read_lines;
+pub struct Day;
 
-pub fn part_1() -> usize {
-    let mut screen = Screen::new(40, Some(20));
+impl Puzzle for Day {
+    const DAY: usize = 10;
+    const TITLE: &'static str = "Cathode-Ray Tube";
 
-    let mut output = 0;
+    type Parsed = Vec<Instruction>;
+    type Params = ();
 
-    for instruction in read_lines(10).iter().map(parse_line) {
-        screen.run_instruction(&instruction);
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
 
-        if let Some(strength) = screen.signal_strength {
-            output += strength;
-        }
+    fn part_1(program: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(program).map(Answer::from)
+    }
+
+    fn part_2(program: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(program).map(Answer::from)
+    }
+
+    fn render(program: &Self::Parsed, _: &()) -> Result<Option<Image>, SolveError> {
+        Ok(Some(render(program)))
     }
 
-    output
+    /// The CRT drawing the letters of part 2.
+    fn animate<'a>(
+        program: &'a Self::Parsed,
+        _: &(),
+        part: usize,
+    ) -> Result<Option<Animation<'a>>, SolveError> {
+        Ok((part == 2).then(|| Box::new(simulation(program, part).map_state(frame)) as Animation))
+    }
+}
+
+pub fn part_1(program: &[Instruction]) -> Result<usize, SolveError> {
+    let mut cpu = simulation(program, 1);
+    cpu.run();
+
+    Ok(cpu.signal_strength)
+}
+
+pub fn part_2(program: &[Instruction]) -> Result<String, SolveError> {
+    ocr::read(&display(program))
+}
+
+/// The pixels the program lights up on the CRT.
+pub fn display(program: &[Instruction]) -> Grid<bool> {
+    let mut cpu = simulation(program, 2);
+    cpu.run();
+
+    cpu.screen.pixels()
 }
 
-pub fn part_2() {
-    let mut screen = Screen::new(40, None);
+/// The program run one instruction at a time, reading the signal strength for `part` 1.
+pub fn simulation(program: &[Instruction], part: usize) -> Cpu<'_> {
+    // The strength is read at cycles 20, 60, 100..., the ends of lines when the first one is 20 wide
+    let first_line_width = (part == 1).then_some(20);
 
-    for instruction in read_lines(10).iter().map(parse_line) {
-        screen.run_instruction(&instruction);
+    Cpu {
+        screen: Screen::new(40, first_line_width),
+        program,
+        next: 0,
+        signal_strength: 0,
     }
+}
 
-    screen.visualize();
+/// The CRT with its lit pixels glowing green.
+pub fn render(program: &[Instruction]) -> Image {
+    Image::from_grid(&display(program), |_, lit| {
+        if *lit {
+            Rgb(96, 255, 96)
+        } else {
+            Rgb(8, 24, 8)
+        }
+    })
+    .with_scale(8)
+}
+
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(input, parse_line)
 }
 
 struct Screen {
@@ -95,24 +159,402 @@ impl Screen {
         }
     }
 
-    fn visualize(&self) {
-        for line in &self.pixels.iter().chunks(self.width) {
-            let output_line: String = line.map(|pixel| if *pixel { '#' } else { ' ' }).collect();
-            println!("{output_line}");
+    fn pixels(&self) -> Grid<bool> {
+        let rows = self
+            .pixels
+            .chunks(self.width)
+            .map(|row| {
+                let mut row = row.to_vec();
+                row.resize(self.width, false);
+                row
+            })
+            .collect();
+
+        Grid::from_rows(rows).unwrap()
+    }
+}
+
+/// The CPU running one instruction at a time, with the CRT drawing along.
+pub struct Cpu<'a> {
+    screen: Screen,
+    program: &'a [Instruction],
+    next: usize,
+    signal_strength: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct CpuState {
+    pub cycle: usize,
+    /// The X register, the middle of the sprite.
+    pub x: usize,
+    /// The sum of the signal strengths read so far.
+    pub signal_strength: usize,
+    pub pixels: Grid<bool>,
+}
+
+impl Simulation for Cpu<'_> {
+    type State = CpuState;
+
+    fn step(&mut self) {
+        self.screen.run_instruction(&self.program[self.next]);
+        self.signal_strength += self.screen.signal_strength.unwrap_or(0);
+        self.next += 1;
+    }
+
+    fn is_done(&self) -> bool {
+        self.next == self.program.len()
+    }
+
+    fn state(&self) -> CpuState {
+        CpuState {
+            cycle: self.screen.cycles,
+            x: self.screen.beam_position,
+            signal_strength: self.signal_strength,
+            pixels: self.screen.pixels(),
         }
     }
 }
 
+/// The pixels drawn so far, with the sprite under the screen.
+fn frame(cpu: &Cpu) -> Frame {
+    let mut cells = SparseGrid::new();
+    let screen = &cpu.screen;
+    let height = screen.pixels.len().div_ceil(screen.width);
+
+    for index in 0..height * screen.width {
+        let char = match screen.pixels.get(index) {
+            None => ' ',
+            Some(true) => '#',
+            Some(false) => '.',
+        };
+        let (x, y) = (index % screen.width, index / screen.width);
+
+        cells.insert(Point::new(x as isize, y as isize), char);
+    }
+
+    let x = screen.beam_position as isize;
+
+    for x in x - 1..=x + 1 {
+        cells.insert(Point::new(x, height as isize + 1), '^');
+    }
+
+    Frame {
+        caption: format!("cycle {}, X = {}", screen.cycles, screen.beam_position),
+        cells,
+        focus: Point::default(),
+    }
+}
+
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Noop,
     Add(isize),
 }
 
-fn parse_line(line: &String) -> Instruction {
-    match &line[0..4] {
-        "noop" => Instruction::Noop,
-        "addx" => Instruction::Add(line[5..].parse::<isize>().unwrap()),
-        _ => unreachable!(),
+fn parse_line(line: &str) -> Result<Instruction, ParseError> {
+    complete(line, |cursor| match cursor.one_of(&["addx ", "noop"])? {
+        "noop" => Ok(Instruction::Noop),
+        _ => Ok(Instruction::Add(cursor.signed()?)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(13140));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let expected = "\
+##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....";
+
+        let pixels = display(&parse(EXAMPLE).unwrap());
+
+        assert_eq!(ocr::draw(&pixels), expected);
+        assert!(matches!(
+            part_2(&parse(EXAMPLE).unwrap()),
+            Err(SolveError::NoAnswer(_))
+        ));
+
+        let image = render(&parse(EXAMPLE).unwrap());
+        assert_eq!((image.width(), image.height()), (320, 48));
+    }
+
+    /// A program lighting exactly the `#` pixels of `art`, searched cycle by cycle from X = 1.
+    fn drawing(art: &str) -> Vec<Instruction> {
+        let pixels = art.lines().flat_map(str::chars).collect::<Vec<_>>();
+        let fits = |cycle: usize, x: isize| {
+            let lit = (x - (cycle % 40) as isize).abs() <= 1;
+            pixels.get(cycle).is_none_or(|pixel| (*pixel == '#') == lit)
+        };
+
+        // The step that first reached each cycle and X, to trace the program back from the end
+        let mut previous = HashMap::from([((0, 1), None)]);
+        let mut queue = VecDeque::from([(0, 1)]);
+
+        while let Some((cycle, x)) = queue.pop_front() {
+            if cycle >= pixels.len() {
+                let mut program = vec![];
+                let mut state = (cycle, x);
+
+                while let Some(Some((before, instruction))) = previous.remove(&state) {
+                    program.push(instruction);
+                    state = before;
+                }
+
+                program.reverse();
+                return program;
+            }
+
+            if !fits(cycle, x) {
+                continue;
+            }
+
+            let mut next = vec![((cycle + 1, x), Instruction::Noop)];
+
+            if fits(cycle + 1, x) {
+                next.extend((0..=41).map(|to| ((cycle + 2, to), Instruction::Add(to - x))));
+            }
+
+            for (state, instruction) in next {
+                previous.entry(state).or_insert_with(|| {
+                    queue.push_back(state);
+                    Some(((cycle, x), instruction))
+                });
+            }
+        }
+
+        panic!("no program draws the art");
+    }
+
+    #[test]
+    fn reads_the_letters_on_the_crt() {
+        let program = drawing(
+            "\
+####.#..#.####.#....###...##..###..#..#.
+#....#..#....#.#....#..#.#..#.#..#.#..#.
+###..####...#..#....#..#.#....#..#.#..#.
+#....#..#..#...#....###..#....###..#..#.
+#....#..#.#....#....#....#..#.#.#..#..#.
+####.#..#.####.####.#.....##..#..#..##..",
+        );
+
+        assert_eq!(part_2(&program), Ok("EHZLPCRU".to_owned()));
+    }
+
+    #[test]
+    fn reads_the_large_letters() {
+        let pixels = Grid::parse(
+            "\
+######...####...#....#..#####...
+#.......#....#..#...#...#....#..
+#.......#.......#..#....#....#..
+#.......#.......#.#.....#....#..
+#####...#.......##......#####...
+#.......#..###..##......#..#....
+#.......#....#..#.#.....#...#...
+#.......#....#..#..#....#...#...
+#.......#...##..#...#...#....#..
+#........###.#..#....#..#....#..",
+            |char| Some(char == '#'),
+        )
+        .unwrap();
+
+        assert_eq!(ocr::read(&pixels), Ok("FGKR".to_owned()));
+    }
+
+    #[test]
+    fn snapshots_every_instruction() {
+        let program = parse(EXAMPLE).unwrap();
+        let snapshots = simulation(&program, 1).snapshots().collect::<Vec<_>>();
+
+        assert_eq!(snapshots.len(), program.len() + 1);
+        assert_eq!((snapshots[2].cycle, snapshots[2].x), (4, 5));
+        assert_eq!(snapshots.last().unwrap().signal_strength, 13140);
+    }
+
+    #[test]
+    fn animates_the_crt() {
+        let program = parse(EXAMPLE).unwrap();
+        let mut cpu = simulation(&program, 2);
+
+        for _ in 0..5 {
+            cpu.step();
+        }
+
+        let frame = frame(&cpu).crop(40, 9);
+
+        assert_eq!(frame[0], "cycle 10, X = 13");
+        assert_eq!(frame[1], format!("##..##..##{}", " ".repeat(30)));
+        assert_eq!(frame[3], format!("{}^^^{}", " ".repeat(12), " ".repeat(25)));
+    }
+
+    #[test]
+    fn reports_malformed_instructions() {
+        let err = parse("noop\naddx five\n").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 6: expected a number, got 'five'\n   |\n 2 | addx five\n   |      ^"
+        );
     }
 }
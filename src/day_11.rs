@@ -1,128 +1,248 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use regex::Regex;
 
-use crate::utils::read_input_split_by_lines_number;
-
-pub fn part_1() -> usize {
-    iterate(20, true)
+use crate::error::{ParseError, SolveError};
+use crate::params::params;
+use crate::simulation::Simulation;
+use crate::solution::{Answer, Puzzle};
+use crate::trace::trace;
+use crate::utils::parse::pattern;
+use crate::utils::parse_records;
+
+params! {
+    pub struct Params {
+        pub part_1_rounds: u32 = 20,
+        pub part_2_rounds: u32 = 10_000,
+    }
 }
 
-pub fn part_2() -> usize {
-    iterate(10000, false)
-}
+pub struct Day;
 
-fn iterate(rounds: u32, reduce_worrying: bool) -> usize {
-    let mut monkeys = read_input_split_by_lines_number(11, 7)
-        .iter()
-        .map(|input| parse_monkey(input, reduce_worrying))
-        .collect_vec();
+impl Puzzle for Day {
+    const DAY: usize = 11;
+    const TITLE: &'static str = "Monkey in the Middle";
 
-    let divisors = monkeys.iter().map(|m| m.divisor).collect_vec();
+    type Parsed = Vec<Monkey>;
+    type Params = Params;
 
-    let mut modulos_vec = vec![];
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
 
-    for (index, monkey) in monkeys.iter().enumerate() {
-        for item in &monkey.items {
-            let mut modulos = Modulos::new(&divisors);
-            modulos.update(&(Operation::Sum, *item));
-            modulos_vec.push((index, modulos));
-        }
+    fn part_1(monkeys: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_1(monkeys, params).map(Answer::from)
     }
 
-    for (index, modulos) in modulos_vec {
-        monkeys[index].modulos.push(modulos);
+    fn part_2(monkeys: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_2(monkeys, params).map(Answer::from)
     }
+}
 
-    let mut all_updates: HashMap<usize, Vec<Modulos>> = HashMap::new();
+pub fn part_1(monkeys: &[Monkey], params: &Params) -> Result<usize, SolveError> {
+    iterate(monkeys, params.part_1_rounds, true)
+}
 
-    for _ in 0..rounds {
-        for (index, monkey) in monkeys.iter_mut().enumerate() {
-            monkey.with_updates(all_updates.get_mut(&index));
+pub fn part_2(monkeys: &[Monkey], params: &Params) -> Result<usize, SolveError> {
+    iterate(monkeys, params.part_2_rounds, false)
+}
 
-            for (monkey, item) in monkey.turn() {
-                all_updates.entry(monkey).or_default().push(item);
-            }
-        }
+/// The monkeys of `part` playing one round at a time.
+pub fn simulation(monkeys: &[Monkey], params: &Params, part: usize) -> Troop {
+    match part {
+        1 => Troop::new(monkeys, params.part_1_rounds, true),
+        _ => Troop::new(monkeys, params.part_2_rounds, false),
     }
+}
 
-    for (index, monkey) in monkeys.iter_mut().enumerate() {
-        monkey.with_updates(all_updates.get_mut(&index));
-    }
+fn iterate(monkeys: &[Monkey], rounds: u32, reduce_worrying: bool) -> Result<usize, SolveError> {
+    let mut troop = Troop::new(monkeys, rounds, reduce_worrying);
+    troop.try_run()?;
 
-    let mut items_inspected = monkeys.iter().map(|monkey| monkey.inspected).collect_vec();
+    let mut items_inspected = troop.state().inspected;
     items_inspected.sort();
     items_inspected.reverse();
 
-    items_inspected[0] * items_inspected[1]
+    Ok(items_inspected[0] * items_inspected[1])
 }
 
-fn parse_monkey(input: &str, reduce_worrying: bool) -> Monkey {
-    let (if_true, if_false) = parse_throws(input);
+pub struct Troop {
+    monkeys: Vec<Monkey>,
+    // The items thrown to each monkey since its last turn
+    updates: HashMap<usize, Vec<Modulos>>,
+    round: u32,
+    rounds: u32,
+}
 
-    Monkey {
-        modulos: vec![],
-        items: parse_items(input),
-        operation: parse_operation(input),
-        divisor: parse_test(input),
-        if_true,
-        if_false,
-        inspected: 0,
-        reduce_worrying,
+impl Troop {
+    fn new(monkeys: &[Monkey], rounds: u32, reduce_worrying: bool) -> Self {
+        let mut monkeys = monkeys.to_vec();
+
+        let divisors = monkeys.iter().map(|m| m.divisor).collect_vec();
+
+        let mut modulos_vec = vec![];
+
+        for (index, monkey) in monkeys.iter().enumerate() {
+            for item in &monkey.items {
+                let modulos = Modulos::new(&divisors, *item, reduce_worrying);
+                modulos_vec.push((index, modulos));
+            }
+        }
+
+        for (index, modulos) in modulos_vec {
+            monkeys[index].modulos.push(modulos);
+        }
+
+        Self {
+            monkeys,
+            updates: HashMap::new(),
+            round: 0,
+            rounds,
+        }
     }
 }
 
-fn parse_items(input: &str) -> Vec<ItemType> {
-    let template = Regex::new(r"Starting items: ((?:\d+, )*\d+)").unwrap();
-    let captures = template.captures(input).unwrap();
-    captures[1]
-        .split(", ")
-        .map(|num| num.parse::<ItemType>().unwrap())
-        .collect_vec()
+#[derive(Clone, Debug, PartialEq)]
+pub struct Round {
+    /// The number of rounds played.
+    pub round: u32,
+    /// The number of items each monkey inspected so far.
+    pub inspected: Vec<usize>,
+    /// The number of items each monkey holds.
+    pub holding: Vec<usize>,
 }
 
-fn parse_operation(input: &str) -> (Operation, ItemType) {
-    let template = Regex::new(r"Operation: new = old ([+*]) ((:?\d+)|(:?\w+))").unwrap();
-    let captures = template.captures(input).unwrap();
+impl Simulation for Troop {
+    type State = Round;
 
-    if &captures[2] == "old" {
-        return match &captures[1] {
-            "*" => (Operation::Power, 0),
-            _ => unreachable!(),
-        };
+    fn step(&mut self) {
+        for (index, monkey) in self.monkeys.iter_mut().enumerate() {
+            monkey.with_updates(self.updates.get_mut(&index));
+
+            for (monkey, item) in monkey.turn() {
+                self.updates.entry(monkey).or_default().push(item);
+            }
+        }
+
+        self.round += 1;
+        trace!(
+            "round {}: inspected {:?}",
+            self.round,
+            self.monkeys.iter().map(|monkey| monkey.inspected).collect_vec()
+        );
     }
 
-    let value = captures[2].parse::<ItemType>().unwrap();
+    fn is_done(&self) -> bool {
+        self.round >= self.rounds
+    }
 
-    match &captures[1] {
-        "+" => (Operation::Sum, value),
-        "*" => (Operation::Multiply, value),
-        _ => unreachable!(),
+    fn state(&self) -> Round {
+        Round {
+            round: self.round,
+            inspected: self.monkeys.iter().map(|monkey| monkey.inspected).collect(),
+            holding: self
+                .monkeys
+                .iter()
+                .enumerate()
+                .map(|(index, monkey)| {
+                    monkey.modulos.len() + self.updates.get(&index).map_or(0, Vec::len)
+                })
+                .collect(),
+        }
     }
 }
 
-fn parse_test(input: &str) -> ItemType {
-    let template = Regex::new(r"Test: divisible by (\d+)").unwrap();
-    let captures = template.captures(input).unwrap();
+pub fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let monkeys = parse_records(input, |record| parse_monkey(&record.text(), record.first_line))?;
 
-    captures[1].parse::<ItemType>().unwrap()
-}
+    if monkeys.len() < 2 {
+        return Err(ParseError::new("expected at least two monkeys"));
+    }
 
-fn parse_throws(input: &str) -> (usize, usize) {
-    let template =
-        Regex::new(r"If true: throw to monkey (\d+)\s+If false: throw to monkey (\d+)").unwrap();
-    let captures = template.captures(input).unwrap();
+    for (index, monkey) in monkeys.iter().enumerate() {
+        let target = monkey.if_true.max(monkey.if_false);
 
-    let if_true = captures[1].parse::<usize>().unwrap();
-    let if_false = captures[2].parse::<usize>().unwrap();
+        if target >= monkeys.len() {
+            return Err(ParseError::new(format!(
+                "monkey {index} throws to monkey {target}, but there are only {}",
+                monkeys.len()
+            )));
+        }
+    }
 
-    (if_true, if_false)
+    Ok(monkeys)
+}
+
+fn parse_monkey(input: &str, first_line: usize) -> Result<Monkey, ParseError> {
+    let missing = |what: &str| {
+        let header = input.lines().next().unwrap_or_default();
+
+        ParseError::new(format!("monkey is missing '{what}'")).in_line(first_line, header)
+    };
+    let number = |part: &str| -> Result<ItemType, ParseError> {
+        part.parse::<ItemType>().map_err(|_| {
+            ParseError::new(format!("expected a number, got '{part}'"))
+                .at_within(input, part, first_line)
+        })
+    };
+
+    let template = pattern!(r"Starting items: ((?:\d+, )*\d+)");
+    let captures = template
+        .captures(input)
+        .ok_or_else(|| missing("Starting items: ..."))?;
+    let items = captures
+        .get(1)
+        .unwrap()
+        .as_str()
+        .split(", ")
+        .map(number)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let template = pattern!(r"Operation: new = old ([+*]) (\w+)");
+    let captures = template
+        .captures(input)
+        .ok_or_else(|| missing("Operation: new = old ..."))?;
+    let operation = match (&captures[1], captures.get(2).unwrap().as_str()) {
+        ("*", "old") => (Operation::Power, 0),
+        ("+", "old") => (Operation::Multiply, 2),
+        ("+", value) => (Operation::Sum, number(value)?),
+        (_, value) => (Operation::Multiply, number(value)?),
+    };
+
+    let template = pattern!(r"Test: divisible by (\w+)");
+    let captures = template
+        .captures(input)
+        .ok_or_else(|| missing("Test: divisible by ..."))?;
+    let divisor_text = captures.get(1).unwrap().as_str();
+    let divisor = number(divisor_text)?;
+
+    if divisor == 0 {
+        return Err(ParseError::new("cannot test divisibility by 0")
+            .at_within(input, divisor_text, first_line));
+    }
+
+    let template =
+        pattern!(r"If true: throw to monkey (\w+)\s+If false: throw to monkey (\w+)");
+    let captures = template
+        .captures(input)
+        .ok_or_else(|| missing("If true: throw to monkey ..."))?;
+    let if_true = number(captures.get(1).unwrap().as_str())?;
+    let if_false = number(captures.get(2).unwrap().as_str())?;
+
+    Ok(Monkey {
+        modulos: vec![],
+        items,
+        operation,
+        divisor,
+        if_true,
+        if_false,
+        inspected: 0,
+    })
 }
 
 type ItemType = usize;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum Operation {
     Sum,
     Multiply,
@@ -132,19 +252,37 @@ enum Operation {
 #[derive(Clone, Debug)]
 struct Modulos {
     data: HashMap<ItemType, ItemType>,
+    // The exact worry level, only tracked while it gets divided by 3 after each inspection
+    value: Option<ItemType>,
 }
 
 impl Modulos {
-    fn new(divisors: &[ItemType]) -> Self {
+    fn new(divisors: &[ItemType], item: ItemType, reduce_worrying: bool) -> Self {
         let mut data = HashMap::new();
         for div in divisors {
-            data.insert(*div, 0);
+            data.insert(*div, item % div);
         }
 
-        Self { data }
+        let value = if reduce_worrying { Some(item) } else { None };
+
+        Self { data, value }
     }
 
     fn update(&mut self, operation: &(Operation, ItemType)) {
+        if let Some(value) = self.value.as_mut() {
+            *value = match &operation.0 {
+                Operation::Sum => *value + operation.1,
+                Operation::Multiply => *value * operation.1,
+                Operation::Power => *value * *value,
+            } / 3;
+
+            for (key, modulo) in self.data.iter_mut() {
+                *modulo = *value % key;
+            }
+
+            return;
+        }
+
         for (key, value) in self.data.iter_mut() {
             match &operation.0 {
                 Operation::Sum => *value = (*value + operation.1) % key,
@@ -155,8 +293,8 @@ impl Modulos {
     }
 }
 
-#[derive(Debug)]
-struct Monkey {
+#[derive(Clone, Debug)]
+pub struct Monkey {
     modulos: Vec<Modulos>,
     items: Vec<ItemType>,
     operation: (Operation, ItemType),
@@ -164,7 +302,6 @@ struct Monkey {
     if_true: usize,
     if_false: usize,
     inspected: usize,
-    reduce_worrying: bool,
 }
 
 impl Monkey {
@@ -199,3 +336,77 @@ impl Monkey {
         (throws_to, item)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(10605));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(2713310158));
+    }
+
+    #[test]
+    fn snapshots_every_round() {
+        let monkeys = parse(EXAMPLE).unwrap();
+        let snapshots = simulation(&monkeys, &Params::default(), 1)
+            .snapshots()
+            .collect_vec();
+
+        assert_eq!(snapshots.len(), 21);
+        assert_eq!(snapshots[0].holding, [2, 4, 3, 1]);
+        assert_eq!(snapshots[1].holding, [4, 6, 0, 0]);
+        assert_eq!(snapshots[20].inspected, [101, 95, 7, 105]);
+    }
+
+    #[test]
+    fn rejects_a_zero_divisor() {
+        let input = EXAMPLE.replacen("divisible by 19", "divisible by 0", 1);
+
+        assert_eq!(
+            parse(&input).unwrap_err().to_string(),
+            "record 2, line 11, column 22: cannot test divisibility by 0\n    |\n 11 |   Test: divisible by 0\n    |                      ^"
+        );
+    }
+
+    #[test]
+    fn parses_records_with_crlf_and_trailing_whitespace() {
+        let input = EXAMPLE.replace('\n', " \r\n").replace("\r\n \r\n", "\r\n\r\n\r\n");
+
+        assert_eq!(part_1(&parse(&input).unwrap(), &Params::default()), Ok(10605));
+    }
+}
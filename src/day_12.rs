@@ -1,198 +1,159 @@
-use std::collections::HashSet;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::grid::{Grid, Pos};
+use crate::utils::image::{Image, Rgb};
+use crate::utils::search::{bfs, Graph, Path};
 
-use itertools::Itertools;
+pub struct Day;
 
-use crate::utils::read_lines;
+impl Puzzle for Day {
+    const DAY: usize = 12;
+    const TITLE: &'static str = "Hill Climbing Algorithm";
 
-pub fn part_1() -> usize {
-    let map = Map::new(true);
-    iterate(&map).path.len() - 1
+    type Parsed = Map;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(map: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(map).map(Answer::from)
+    }
+
+    fn part_2(map: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(map).map(Answer::from)
+    }
+
+    fn render(map: &Self::Parsed, _: &()) -> Result<Option<Image>, SolveError> {
+        render(map).map(Some)
+    }
 }
 
-pub fn part_2() -> usize {
-    let map = Map::new(false);
-    iterate(&map).path.len() - 1
+pub fn part_1(map: &Map) -> Result<usize, SolveError> {
+    shortest_path(map, [map.start])
 }
 
-fn iterate(map: &Map) -> Path {
-    let mut paths = vec![Path::new(&map)];
-    let mut next_paths = vec![];
-    let mut visited = HashSet::new();
-
-    let test: fn(&Path, &Map) -> bool = if map.forward {
-        |path, map| path.current.0 == map.end
-    } else {
-        |path, _| path.current.1 == 'a' as u32
-    };
-
-    loop {
-        for path in paths {
-            let tmp = path
-                .find_more_paths(&map)
-                .into_iter()
-                .filter(|path| !visited.iter().contains(&path.current.0))
-                .collect_vec();
-
-            tmp.iter().for_each(|path| {
-                visited.insert(path.current.0.clone());
-            });
-
-            next_paths.extend(tmp);
-        }
-
-        if let Some(found) = next_paths.iter().find(|path| test(*path, &map)) {
-            return found.clone();
-        }
-
-        paths = next_paths;
-        next_paths = vec![];
+pub fn part_2(map: &Map) -> Result<usize, SolveError> {
+    let lowest = map
+        .heights
+        .iter()
+        .filter(|(_, height)| **height == 'a' as u32)
+        .map(|(pos, _)| pos);
+
+    shortest_path(map, lowest)
+}
+
+/// The heightmap from green valleys to snowy peaks, with the part 1 path in red.
+pub fn render(map: &Map) -> Result<Image, SolveError> {
+    let path = find_path(map, [map.start])?;
+
+    let mut image = Image::from_grid(&map.heights, |_, height| {
+        let height = (*height - 'a' as u32) as f64 / 25.0;
+        Rgb(32, 96, 48).mix(Rgb(240, 240, 240), height)
+    });
+
+    for pos in path.nodes {
+        image.set(pos, Rgb(224, 48, 32));
     }
+
+    Ok(image.with_scale(6))
+}
+
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    Map::new(input)
 }
 
-struct Map {
-    map: Vec<Vec<u32>>,
-    max: Coord,
-    start: Coord,
-    end: Coord,
-    forward: bool,
+fn shortest_path<S>(map: &Map, starts: S) -> Result<usize, SolveError>
+where
+    S: IntoIterator<Item = Pos>,
+{
+    find_path(map, starts).map(|path| path.cost)
+}
+
+fn find_path<S>(map: &Map, starts: S) -> Result<Path<Pos>, SolveError>
+where
+    S: IntoIterator<Item = Pos>,
+{
+    bfs(map, starts, |pos| *pos == map.end)
+        .ok_or_else(|| SolveError::no_answer("the end 'E' cannot be reached"))
+}
+
+pub struct Map {
+    heights: Grid<u32>,
+    start: Pos,
+    end: Pos,
 }
 
 impl Map {
-    fn new(forward: bool) -> Self {
-        let mut map = vec![];
-        let mut start = Coord { x: 0, y: 0 };
-        let mut end = Coord { x: 0, y: 0 };
-        let mut max = Coord { x: 0, y: 0 };
-        read_lines(12)
-            .iter()
-            .map(|line| line.chars().collect_vec())
-            .enumerate()
-            .for_each(|(y, chars)| {
-                max.y = max.y.max(y);
-
-                let mut line = vec![];
-                for (x, char) in chars.iter().enumerate() {
-                    max.x = max.x.max(x);
-
-                    match char {
-                        'a'..='z' => line.push(*char as u32),
-                        'S' => {
-                            line.push('a' as u32);
-                            start = Coord { x, y };
-                        }
-                        'E' => {
-                            line.push('z' as u32);
-                            end = Coord { x, y };
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-
-                map.push(line);
-            });
-
-        Map {
-            map,
+    fn new(input: &str) -> Result<Self, ParseError> {
+        let map = Grid::parse(input, |char| {
+            matches!(char, 'a'..='z' | 'S' | 'E').then_some(char)
+        })?;
+
+        let start = map
+            .position(|char| *char == 'S')
+            .ok_or_else(|| ParseError::new("the map has no start 'S'"))?;
+        let end = map
+            .position(|char| *char == 'E')
+            .ok_or_else(|| ParseError::new("the map has no end 'E'"))?;
+
+        let heights = map.map(|_, char| match char {
+            'S' => 'a' as u32,
+            'E' => 'z' as u32,
+            _ => *char as u32,
+        });
+
+        Ok(Map {
+            heights,
             start,
             end,
-            max,
-            forward,
-        }
+        })
     }
-
-    fn at(&self, coord: &Coord) -> u32 {
-        self.map[coord.y][coord.x]
-    }
-}
-
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Coord {
-    x: usize,
-    y: usize,
 }
 
-impl Coord {
-    fn neighbors(&self, max: &Coord) -> Vec<Self> {
-        let mut output = vec![];
+impl Graph for Map {
+    type Node = Pos;
 
-        if self.x > 0 {
-            output.push(self.with_x(self.x - 1));
-        }
+    fn neighbours(&self, pos: &Pos) -> impl Iterator<Item = Pos> {
+        let height = self.heights[*pos];
 
-        if self.x < max.x {
-            output.push(self.with_x(self.x + 1));
-        }
+        self.heights
+            .neighbours(*pos)
+            .filter(move |next| self.heights[*next] <= height + 1)
+    }
+}
 
-        if self.y > 0 {
-            output.push(self.with_y(self.y - 1));
-        }
+#[cfg(test)]
+mod tests {
+    use crate::utils::point::Point;
 
-        if self.y < max.y {
-            output.push(self.with_y(self.y + 1));
-        }
+    use super::*;
 
-        output
-    }
+    const EXAMPLE: &str = "\
+Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi";
 
-    fn with_x(&self, x: usize) -> Self {
-        let mut coord = self.clone();
-        coord.x = x;
-        coord
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(31));
     }
 
-    fn with_y(&self, y: usize) -> Self {
-        let mut coord = self.clone();
-        coord.y = y;
-        coord
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(29));
     }
-}
 
-#[derive(Clone, Debug)]
-struct Path {
-    visited: HashSet<Coord>,
-    path: Vec<Coord>,
-    current: (Coord, u32),
-}
-
-impl Path {
-    fn new(map: &Map) -> Self {
-        let start = if map.forward {
-            map.start.clone()
-        } else {
-            map.end.clone()
-        };
-        let mut visited = HashSet::new();
-        visited.insert(start.clone());
-
-        Self {
-            visited,
-            path: vec![start.clone()],
-            current: (start.clone(), map.at(&start)),
-        }
-    }
+    #[test]
+    fn renders_the_path_over_the_heights() {
+        let image = render(&parse(EXAMPLE).unwrap()).unwrap();
 
-    fn find_more_paths(self, map: &Map) -> Vec<Self> {
-        self.current
-            .0
-            .neighbors(&map.max)
-            .into_iter()
-            .filter(|coord| !self.visited.iter().contains(&coord))
-            .filter(|coord| {
-                let height = map.at(coord);
-                if map.forward {
-                    height <= self.current.1 + 1
-                } else {
-                    height >= self.current.1 - 1
-                }
-            })
-            .map(|coord| {
-                let mut path = self.clone();
-                path.visited.insert(coord.clone());
-                path.path.push(coord.clone());
-                path.current = (coord.clone(), map.at(&coord));
-
-                path
-            })
-            .collect_vec()
+        assert_eq!((image.width(), image.height()), (48, 30));
+        assert_eq!(image.get(Point::new(0, 0)), Some(Rgb(224, 48, 32)));
+        assert_eq!(image.get(Point::new(5, 2)), Some(Rgb(224, 48, 32)));
+        assert_eq!(image.get(Point::new(0, 2)), Some(Rgb(32, 96, 48)));
     }
 }
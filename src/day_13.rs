@@ -1,29 +1,49 @@
 use std::fmt;
 
-use crate::utils::{read_input_split_by_lines_number, read_lines};
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::utils::{parse_number, parse_records, Record};
 use itertools::Itertools;
 
-pub fn part_1() -> usize {
-    read_input_split_by_lines_number(13, 3)
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 13;
+    const TITLE: &'static str = "Distress Signal";
+
+    type Parsed = Vec<Pair>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(pairs: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(pairs).map(Answer::from)
+    }
+
+    fn part_2(pairs: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(pairs).map(Answer::from)
+    }
+}
+
+pub fn part_1(pairs: &[Pair]) -> Result<usize, SolveError> {
+    Ok(pairs
         .iter()
-        .map(|input| parse_pair(input))
-        .map(|(left, right)| compare(&left, &right))
+        .map(|(left, right)| compare(left, right))
         .enumerate()
         .filter(|(_, comparison)| comparison == &Comparison::Ok)
         .map(|(index, _)| index + 1)
-        .sum()
+        .sum())
 }
 
-pub fn part_2() -> usize {
-    let input = read_lines(13);
-    let (two_value, _) = parse("[[2]]");
-    let (six_value, _) = parse("[[6]]");
+pub fn part_2(pairs: &[Pair]) -> Result<usize, SolveError> {
+    let two_value = parse_packet("[[2]]")?;
+    let six_value = parse_packet("[[6]]")?;
 
-    let mut tmp = input
+    let mut tmp = pairs
         .iter()
-        .filter(|line| !line.is_empty())
-        .map(|line| parse(line))
-        .map(|(value, _)| value)
+        .flat_map(|(left, right)| [left.clone(), right.clone()])
         .collect_vec();
 
     tmp.push(two_value.clone());
@@ -31,9 +51,15 @@ pub fn part_2() -> usize {
 
     let tmp = sort(tmp);
 
-    get_value_index(&two_value, &tmp) * get_value_index(&six_value, &tmp)
+    Ok(get_value_index(&two_value, &tmp) * get_value_index(&six_value, &tmp))
 }
 
+pub fn parse(input: &str) -> Result<Vec<Pair>, ParseError> {
+    parse_records(input, parse_pair)
+}
+
+pub type Pair = (Value, Value);
+
 fn get_value_index(lookup_value: &Value, list: &[Value]) -> usize {
     let (index, _) = list
         .iter()
@@ -45,9 +71,9 @@ fn get_value_index(lookup_value: &Value, list: &[Value]) -> usize {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-enum Value {
+pub enum Value {
     Number(usize),
-    List(Box<Vec<Value>>),
+    List(Vec<Value>),
 }
 
 impl fmt::Display for Value {
@@ -63,71 +89,84 @@ impl fmt::Display for Value {
 }
 
 #[derive(Debug, PartialEq)]
-enum Comparison {
+pub enum Comparison {
     Ok,
     NotOk,
     Next,
 }
 
-fn parse_pair(input: &str) -> (Value, Value) {
-    let lines = input.lines().collect_vec();
+fn parse_pair(record: &Record) -> Result<Pair, ParseError> {
+    let packets = record.parse_lines(parse_packet)?;
+
+    match <[Value; 2]>::try_from(packets) {
+        Ok([left, right]) => Ok((left, right)),
+        Err(packets) => Err(ParseError::new(format!(
+            "expected a pair of packets, got {}",
+            packets.len()
+        ))
+        .in_line(record.first_line, record.lines[0])),
+    }
+}
+
+pub fn parse_packet(line: &str) -> Result<Value, ParseError> {
+    let (value, inspected) = parse_value(line, line)?;
 
-    let left = parse(lines[0]);
-    let right = parse(lines[1]);
+    if inspected < line.len() {
+        let rest = &line[inspected..];
 
-    (left.0, right.0)
+        return Err(ParseError::new("unexpected text after the packet").at(line, rest));
+    }
+
+    Ok(value)
 }
 
-fn parse(input: &str) -> (Value, usize) {
+fn parse_value(line: &str, input: &str) -> Result<(Value, usize), ParseError> {
     if input.starts_with('[') {
-        return get_list(input);
+        return get_list(line, input);
     }
 
-    let mut inspected = 0;
+    let inspected = input
+        .find(|char: char| !char.is_ascii_digit())
+        .unwrap_or(input.len());
 
-    let mut number = String::from("");
-    for (index, char) in input.char_indices() {
-        match char {
-            '0'..='9' => number.push(char),
-            _ => {
-                inspected = index;
-                break;
-            }
-        }
+    if inspected == 0 {
+        return Err(ParseError::new("expected a number or a list").at(line, input));
     }
 
-    let value = Value::Number(number.parse::<usize>().unwrap());
+    let value = Value::Number(parse_number(line, &input[..inspected])?);
 
-    (value, inspected)
+    Ok((value, inspected))
 }
 
-fn get_list(input: &str) -> (Value, usize) {
+fn get_list(line: &str, input: &str) -> Result<(Value, usize), ParseError> {
     let mut inspected = 1;
 
-    let mut output = Box::new(vec![]);
+    let mut output = vec![];
 
     if input.starts_with("[]") {
         inspected += 1;
-        return (Value::List(output), inspected);
+        return Ok((Value::List(output), inspected));
     }
 
     loop {
-        let (value, inspected_inner) = parse(&input[inspected..]);
+        let (value, inspected_inner) = parse_value(line, &input[inspected..])?;
         output.push(value);
         inspected += inspected_inner;
 
-        if &input[inspected..inspected + 1] == "]" {
-            inspected += 1;
-            break;
+        match input[inspected..].chars().next() {
+            Some(']') => {
+                inspected += 1;
+                break;
+            }
+            Some(',') => inspected += 1,
+            _ => return Err(ParseError::new("expected ',' or ']'").at(line, &input[inspected..])),
         }
-
-        inspected += 1;
     }
 
-    (Value::List(output), inspected)
+    Ok((Value::List(output), inspected))
 }
 
-fn compare(left: &Value, right: &Value) -> Comparison {
+pub fn compare(left: &Value, right: &Value) -> Comparison {
     use Comparison::*;
     use Value::*;
 
@@ -152,12 +191,12 @@ fn compare(left: &Value, right: &Value) -> Comparison {
 
             compare(&Number(left.len()), &Number(right.len()))
         }
-        (List(_), Number(_)) => compare(left, &List(Box::new(vec![right.clone()]))),
-        (Number(_), List(_)) => compare(&List(Box::new(vec![left.clone()])), right),
+        (List(_), Number(_)) => compare(left, &List(vec![right.clone()])),
+        (Number(_), List(_)) => compare(&List(vec![left.clone()]), right),
     }
 }
 
-fn sort(mut list: Vec<Value>) -> Vec<Value> {
+pub fn sort(mut list: Vec<Value>) -> Vec<Value> {
     let len = list.len();
 
     if len == 0 || len == 1 {
@@ -194,3 +233,43 @@ fn sort(mut list: Vec<Value>) -> Vec<Value> {
 
     sorted
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(13));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(140));
+    }
+}
@@ -1,67 +1,142 @@
 use core::fmt;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use itertools::Itertools;
 
-use crate::utils::read_lines;
+use crate::animation::{Animation, Frame};
+use crate::error::{ParseError, SolveError};
+use crate::params::params;
+use crate::simulation::Simulation;
+use crate::solution::{Answer, Puzzle};
+use crate::utils::grid::{SparseGrid, SparsePos};
+use crate::utils::image::{Image, Rgb};
+use crate::utils::point::{Direction8, Point};
+use crate::utils::parse::{complete, Cursor};
+use crate::utils::parse_lines;
+
+type WallCorners = Vec<SparsePos>;
+type Pairs = HashSet<(SparsePos, SparsePos)>;
+
+params! {
+    pub struct Params {
+        pub source: SparsePos = Point::new(500, 0),
+    }
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 14;
+    const TITLE: &'static str = "Regolith Reservoir";
+
+    type Parsed = SparseGrid<CellType>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(walls: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_1(walls, params).map(Answer::from)
+    }
+
+    fn part_2(walls: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_2(walls, params).map(Answer::from)
+    }
 
-type WallCorners = Vec<Coord>;
-type Cells = HashMap<Coord, CellType>;
-type Pairs = HashSet<(Coord, Coord)>;
+    fn render(walls: &Self::Parsed, params: &Params) -> Result<Option<Image>, SolveError> {
+        render(walls, params).map(Some)
+    }
 
-pub fn part_1() -> usize {
-    solve(&read_lines(14), BottomType::Void)
+    fn animate<'a>(
+        walls: &'a Self::Parsed,
+        params: &Params,
+        part: usize,
+    ) -> Result<Option<Animation<'a>>, SolveError> {
+        Ok(Some(Box::new(
+            simulation(walls, params, part).map_state(frame),
+        )))
+    }
 }
 
-pub fn part_2() -> usize {
-    solve(&read_lines(14), BottomType::Floor)
+pub fn part_1(walls: &SparseGrid<CellType>, params: &Params) -> Result<usize, SolveError> {
+    solve(walls, params, 1)
 }
 
-fn solve(input: &[String], bottom_type: BottomType) -> usize {
-    let walls = input
-        .iter()
-        .unique()
-        .map(|line| parse_line(line))
-        .collect_vec();
+pub fn part_2(walls: &SparseGrid<CellType>, params: &Params) -> Result<usize, SolveError> {
+    solve(walls, params, 2)
+}
 
-    let pairs = extract_pairs(&walls);
+/// The sand of `part` falling one cell at a time, into the void or onto the floor.
+pub fn simulation(walls: &SparseGrid<CellType>, params: &Params, part: usize) -> Cave {
+    let bottom_type = match part {
+        1 => BottomType::Void,
+        _ => BottomType::Floor,
+    };
 
-    let mut cave: Cave = prepare_cave(pairs, bottom_type);
+    Cave::new(walls.clone(), params.source, bottom_type)
+}
 
-    cave.new_particle();
+/// The cave once the sand of part 2 has piled up to the source.
+pub fn render(walls: &SparseGrid<CellType>, params: &Params) -> Result<Image, SolveError> {
+    let cells = fill(walls, params, 2)?;
 
-    loop {
-        cave.tick();
+    let image = Image::from_sparse(&cells, Rgb(24, 20, 16), |pos, cell| match cell {
+        _ if pos == params.source => Rgb(224, 48, 32),
+        CellType::Wall => Rgb(112, 96, 80),
+        CellType::Sand => Rgb(232, 200, 120),
+    });
 
-        if cave.fullfilled {
-            break;
-        }
+    Ok(image.with_scale(4))
+}
 
-        if let None = cave.current_particle {
-            cave.new_particle();
-        }
-    }
+/// The scanned rock, as wall cells of the cave.
+pub fn parse(input: &str) -> Result<SparseGrid<CellType>, ParseError> {
+    let walls = parse_lines(input, parse_line)?
+        .into_iter()
+        .unique()
+        .collect_vec();
+
+    Ok(prepare_walls(extract_pairs(&walls)))
+}
 
-    cave.cells
+fn solve(walls: &SparseGrid<CellType>, params: &Params, part: usize) -> Result<usize, SolveError> {
+    Ok(fill(walls, params, part)?
         .iter()
-        .filter(|cell| *cell.1 == CellType::Sand)
-        .count()
+        .filter(|(_, cell)| **cell == CellType::Sand)
+        .count())
 }
 
-#[derive(PartialEq)]
-enum CellType {
+/// The cells of the cave once no more sand comes to rest.
+fn fill(
+    walls: &SparseGrid<CellType>,
+    params: &Params,
+    part: usize,
+) -> Result<SparseGrid<CellType>, SolveError> {
+    let mut cave = simulation(walls, params, part);
+    cave.try_run()?;
+
+    Ok(cave.cells)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellType {
     Wall,
     Sand,
 }
 
-impl fmt::Display for CellType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let display_as = match self {
+impl CellType {
+    pub fn symbol(&self) -> char {
+        match self {
             CellType::Sand => 'o',
             CellType::Wall => '#',
-        };
+        }
+    }
+}
 
-        write!(f, "{display_as}")
+impl fmt::Display for CellType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
     }
 }
 
@@ -71,93 +146,74 @@ enum BottomType {
     Void,
 }
 
-struct Cave {
-    cells: Cells,
-    start_coord: Coord,
-    current_particle: Option<Coord>,
+// Sand tries to fall straight down first, then diagonally left, then right
+const FALLING: [Direction8; 3] = [Direction8::Down, Direction8::DownLeft, Direction8::DownRight];
+
+pub struct Cave {
+    cells: SparseGrid<CellType>,
+    source: SparsePos,
+    current_particle: Option<SparsePos>,
     fullfilled: bool,
-    max: Coord,
+    bottom: isize,
+    particles: usize,
 }
 
 impl Cave {
-    fn new(mut cells: Cells, bottom_type: BottomType) -> Self {
-        let wall_cells = cells
-            .iter()
-            .filter(|(_, cell_type)| **cell_type == CellType::Wall)
-            .map(|(coord, _)| coord.clone())
-            .collect_vec();
-
-        let mut max = wall_cells.iter().fold(Coord::min(), Coord::max_of);
+    fn new(mut cells: SparseGrid<CellType>, source: SparsePos, bottom_type: BottomType) -> Self {
+        let mut bottom = cells.bounds().map_or(0, |(_, max)| max.y);
 
         if bottom_type == BottomType::Floor {
-            max.y += 2;
-            let y = max.y;
+            bottom += 2;
 
-            for x in (500 - y - 1)..=(500 + y + 1) {
-                cells.insert(Coord { x, y }, CellType::Wall);
+            for x in (source.x - bottom - 1)..=(source.x + bottom + 1) {
+                cells.insert(Point::new(x, bottom), CellType::Wall);
             }
         }
 
         Self {
             cells,
-            start_coord: Coord { x: 500, y: 0 },
+            source,
             current_particle: None,
             fullfilled: false,
-            max,
+            bottom,
+            particles: 0,
         }
     }
 
     fn new_particle(&mut self) {
-        if self.cells.contains_key(&self.start_coord) {
+        if self.cells.contains(self.source) {
             self.fullfilled = true;
             return;
         }
 
-        self.current_particle = Some(self.start_coord.clone());
-        self.cells.insert(self.start_coord.clone(), CellType::Sand);
+        self.current_particle = Some(self.source);
+        self.cells.insert(self.source, CellType::Sand);
+        self.particles += 1;
     }
 
     fn tick(&mut self) {
-        let current_particle = self
-            .current_particle
-            .clone()
-            .unwrap_or(self.start_coord.clone());
-
-        let next_coord = current_particle.down();
-        let moved = self.move_particle(&current_particle, next_coord);
-
-        if moved {
-            return;
-        }
+        let particle = self.current_particle.unwrap_or(self.source);
 
-        let next_coord = current_particle.down_left();
-        let moved = self.move_particle(&current_particle, next_coord);
-
-        if moved {
-            return;
-        }
-
-        let next_coord = current_particle.down_right();
-        let moved = self.move_particle(&current_particle, next_coord);
-
-        if moved {
-            return;
+        for direction in FALLING {
+            if self.move_particle(particle, particle + direction.delta()) {
+                return;
+            }
         }
 
         self.current_particle = None;
     }
 
-    fn move_particle(&mut self, from: &Coord, to: Coord) -> bool {
-        if from.y >= self.max.y {
+    fn move_particle(&mut self, from: SparsePos, to: SparsePos) -> bool {
+        if from.y >= self.bottom {
             self.fullfilled = true;
             self.current_particle = None;
-            self.cells.remove(&from);
+            self.cells.remove(from);
             return true;
         }
 
-        if !self.cells.contains_key(&to) {
-            let value = self.cells.remove(&from).unwrap();
-            self.cells.insert(to.clone(), value);
+        if !self.cells.contains(to) {
+            let value = self.cells.remove(from).unwrap();
+            self.cells.insert(to, value);
             self.current_particle = Some(to);
             return true;
         }
@@ -166,63 +222,68 @@ impl Cave {
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd)]
-struct Coord {
-    x: usize,
-    y: usize,
+#[derive(Clone, Debug)]
+pub struct CaveState {
+    pub cells: SparseGrid<CellType>,
+    pub source: SparsePos,
+    /// The unit of sand still falling, if any.
+    pub falling: Option<SparsePos>,
+    /// The number of units let out of the source so far.
+    pub units: usize,
 }
 
-impl Coord {
-    fn min() -> Self {
-        Self { x: 0, y: 0 }
-    }
-
-    fn max_of(one: Self, other: &Self) -> Self {
-        Self {
-            x: one.x.max(other.x),
-            y: one.y.max(other.y),
-        }
-    }
+impl Simulation for Cave {
+    type State = CaveState;
 
-    fn down(&self) -> Self {
-        Self {
-            x: self.x,
-            y: self.y + 1,
+    /// Moves the falling sand one cell, or lets the next unit out of the source.
+    fn step(&mut self) {
+        match self.current_particle {
+            Some(_) => self.tick(),
+            None => self.new_particle(),
         }
     }
 
-    fn down_left(&self) -> Self {
-        Self {
-            x: self.x - 1,
-            y: self.y + 1,
-        }
+    fn is_done(&self) -> bool {
+        self.fullfilled
     }
 
-    fn down_right(&self) -> Self {
-        Self {
-            x: self.x + 1,
-            y: self.y + 1,
+    fn state(&self) -> CaveState {
+        CaveState {
+            cells: self.cells.clone(),
+            source: self.source,
+            falling: self.current_particle,
+            units: self.particles,
         }
     }
 }
 
-fn parse_line(line: &str) -> Vec<Coord> {
-    line.split(" -> ")
-        .map(|part| parse_cord(part))
-        .collect_vec()
-}
+fn frame(cave: &Cave) -> Frame {
+    let mut cells = SparseGrid::new();
 
-fn parse_cord(input: &str) -> Coord {
-    let parts = input.split(",").collect_vec();
+    for (pos, cell) in cave.cells.iter() {
+        cells.insert(pos, cell.symbol());
+    }
+
+    if !cave.cells.contains(cave.source) {
+        cells.insert(cave.source, '+');
+    }
 
-    Coord {
-        x: parts[0].parse::<usize>().unwrap(),
-        y: parts[1].parse::<usize>().unwrap(),
+    Frame {
+        caption: format!("unit {}", cave.particles),
+        cells,
+        focus: cave.current_particle.unwrap_or(cave.source),
     }
 }
 
-fn min_max(one: &usize, two: &usize) -> (usize, usize) {
-    (*one.min(two), *one.max(two))
+fn parse_line(line: &str) -> Result<Vec<SparsePos>, ParseError> {
+    complete(line, |cursor| cursor.separated(" -> ", parse_cord))
+}
+
+fn parse_cord(cursor: &mut Cursor) -> Result<SparsePos, ParseError> {
+    Ok(Point::new(
+        cursor.signed()?,
+        cursor.field(",", Cursor::signed)?,
+    ))
 }
 
 fn extract_pairs(walls: &[WallCorners]) -> Pairs {
@@ -230,8 +291,7 @@ fn extract_pairs(walls: &[WallCorners]) -> Pairs {
 
     for wall in walls {
         for pair in wall.windows(2) {
-            let one = pair[0].clone();
-            let two = pair[1].clone();
+            let (one, two) = (pair[0], pair[1]);
 
             pairs.insert(if one > two { (two, one) } else { (one, two) });
         }
@@ -240,26 +300,87 @@ fn extract_pairs(walls: &[WallCorners]) -> Pairs {
     pairs
 }
 
-fn prepare_cave(pairs: Pairs, bottom_type: BottomType) -> Cave {
-    let mut cells = HashMap::new();
+fn prepare_walls(pairs: Pairs) -> SparseGrid<CellType> {
+    let mut cells = SparseGrid::new();
 
     for (from, to) in pairs {
-        if from.x != to.x {
-            let (min, max) = min_max(&from.x, &to.x);
-            let y = from.y;
+        let (min, max) = (from.min(to), from.max(to));
 
-            for x in min..=max {
-                cells.insert(Coord { x, y }, CellType::Wall);
-            }
-        } else if from.x != to.y {
-            let (min, max) = min_max(&from.y, &to.y);
-            let x = from.x;
-
-            for y in min..=max {
-                cells.insert(Coord { x, y }, CellType::Wall);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                cells.insert(Point::new(x, y), CellType::Wall);
             }
         }
     }
 
-    Cave::new(cells, bottom_type)
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(24));
+    }
+
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap(), &Params::default()), Ok(93));
+    }
+
+    #[test]
+    fn renders_the_filled_cave() {
+        let image = render(&parse(EXAMPLE).unwrap(), &Params::default()).unwrap();
+
+        assert_eq!((image.width(), image.height()), (100, 48));
+        assert_eq!(image.get(Point::new(12, 0)), Some(Rgb(224, 48, 32)));
+        assert_eq!(image.get(Point::new(12, 1)), Some(Rgb(232, 200, 120)));
+        assert_eq!(image.get(Point::new(0, 11)), Some(Rgb(112, 96, 80)));
+    }
+
+    #[test]
+    fn snapshots_every_cell_the_sand_falls() {
+        let walls = parse(EXAMPLE).unwrap();
+        let snapshots = simulation(&walls, &Params::default(), 1)
+            .snapshots()
+            .collect_vec();
+
+        assert_eq!(snapshots[1].falling, Some(Point::new(500, 0)));
+        assert_eq!(snapshots[9].falling, Some(Point::new(500, 8)));
+        assert_eq!((snapshots[10].falling, snapshots[10].units), (None, 1));
+        assert_eq!(snapshots.last().unwrap().units, 25);
+    }
+
+    #[test]
+    fn animates_the_falling_sand() {
+        let walls = parse(EXAMPLE).unwrap();
+        let mut cave = simulation(&walls, &Params::default(), 1);
+
+        for _ in 0..10 {
+            cave.step();
+        }
+
+        assert_eq!(
+            frame(&cave).crop(20, 20),
+            [
+                "unit 1",
+                "      +   ",
+                "          ",
+                "          ",
+                "          ",
+                "    #   ##",
+                "    #   # ",
+                "  ###   # ",
+                "        # ",
+                "      o # ",
+                "######### ",
+            ]
+        );
+    }
 }
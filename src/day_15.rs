@@ -1,15 +1,62 @@
-use std::{fmt::Debug, ops::Range};
+use std::ops::Range;
 
 use itertools::Itertools;
-use regex::Regex;
 
-use crate::utils::read_lines;
+use crate::cancel;
+use crate::error::{ParseError, SolveError};
+use crate::params::params;
+use crate::solution::{Answer, Puzzle};
+use crate::trace::debug;
+use crate::utils::image::{Image, Rgb};
+use crate::utils::interval::IntervalSet;
+use crate::utils::point::Point;
+use crate::utils::parse::{complete, Cursor};
+use crate::utils::parse_lines;
+
+params! {
+    pub struct Params {
+        pub y: i32 = 2_000_000,
+        pub max: i32 = 4_000_000,
+    }
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    const DAY: usize = 15;
+    const TITLE: &'static str = "Beacon Exclusion Zone";
 
-pub fn part_1() -> usize {
-    let (sensors, beacons): (Vec<Sensor>, Vec<Coord>) =
-        read_lines(15).iter().map(|line| parse_line(&line)).unzip();
+    type Parsed = Vec<Reading>;
+    type Params = Params;
 
-    let y = 2000000;
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part_1(readings: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_1(readings, params).map(Answer::from)
+    }
+
+    fn part_2(readings: &Self::Parsed, params: &Params) -> Result<Answer, SolveError> {
+        part_2(readings, params).map(Answer::from)
+    }
+
+    fn render(readings: &Self::Parsed, params: &Params) -> Result<Option<Image>, SolveError> {
+        render(readings, params).map(Some)
+    }
+}
+
+const TUNING_MULTIPLIER: u64 = 4_000_000;
+
+/// The largest number of cells drawn along each side of the search area.
+const RENDER_SIZE: usize = 400;
+
+pub fn part_1(readings: &[Reading], params: &Params) -> Result<usize, SolveError> {
+    let y = params.y;
+    let (sensors, beacons): (Vec<&Sensor>, Vec<Point<i32>>) = readings
+        .iter()
+        .map(|(sensor, beacon)| (sensor, *beacon))
+        .unzip();
 
     let beacons_on_line = beacons
         .into_iter()
@@ -17,161 +64,197 @@ pub fn part_1() -> usize {
         .filter(|beacon| beacon.y == y)
         .count();
 
-    let ranges = ranges_for_y(&sensors, &y);
+    let covered = ranges_for_y(sensors, &y).total_length() as usize;
 
-    assert!(ranges.len() == 1);
+    Ok(covered - beacons_on_line)
+}
 
-    ranges[0].len() - beacons_on_line
+pub fn part_2(readings: &[Reading], params: &Params) -> Result<u64, SolveError> {
+    match distress_beacon(readings, params.max)? {
+        Some(beacon) => {
+            debug!("distress beacon at x={}, y={}", beacon.x, beacon.y);
+            Ok(beacon.x as u64 * TUNING_MULTIPLIER + beacon.y as u64)
+        }
+        None => Err(SolveError::no_answer(format!(
+            "the sensors cover every position up to {}, there is no distress beacon",
+            params.max
+        ))),
+    }
 }
 
-pub fn part_2() -> u64 {
-    let (sensors, _): (Vec<Sensor>, Vec<Coord>) =
-        read_lines(15).iter().map(|line| parse_line(&line)).unzip();
+/// The search area with the covered part in blue, the sensors in red, their beacons in yellow
+/// and the distress beacon in white. Large areas are sampled, one cell per block of positions.
+pub fn render(readings: &[Reading], params: &Params) -> Result<Image, SolveError> {
+    let size = params.max.max(0) as usize + 1;
+    let block = size.div_ceil(RENDER_SIZE);
+    let cells = size.div_ceil(block);
+    let cell = |pos: Point<i32>| Point::new(pos.x as usize / block, pos.y as usize / block);
+    let sample = |index: usize| ((index * block + block / 2) as i32).min(params.max);
 
-    let min = 0;
-    let max = 4_000_000;
+    let mut image = Image::new(cells, cells, Rgb(16, 16, 32));
+
+    for row in 0..cells {
+        let y = sample(row);
+        let covered = ranges_for_y(readings.iter().map(|(sensor, _)| sensor), &y);
+
+        for column in 0..cells {
+            if covered.contains(sample(column)) {
+                image.set(Point::new(column, row), Rgb(40, 72, 120));
+            }
+        }
+    }
 
-    for y in min..=max {
-        let ranges = ranges_for_y(&sensors, &y);
+    let area = 0..=params.max;
+    let in_area = |pos: &Point<i32>| area.contains(&pos.x) && area.contains(&pos.y);
 
-        if ranges.len() > 1 {
-            return ranges[0].end as u64 * max as u64 + y as u64;
+    for (sensor, beacon) in readings {
+        for (pos, color) in [
+            (sensor.coord, Rgb(224, 48, 32)),
+            (*beacon, Rgb(240, 208, 64)),
+        ] {
+            if in_area(&pos) {
+                image.set(cell(pos), color);
+            }
         }
     }
 
-    unreachable!()
+    if let Some(beacon) = distress_beacon(readings, params.max)? {
+        image.set(cell(beacon), Rgb::WHITE);
+    }
+
+    Ok(image.with_scale((RENDER_SIZE / cells).max(1)))
 }
 
-#[derive(Clone, Eq, Hash, PartialEq)]
-struct Coord {
-    x: i32,
-    y: i32,
+pub fn parse(input: &str) -> Result<Vec<Reading>, ParseError> {
+    parse_lines(input, parse_line)
 }
 
-struct Sensor {
-    coord: Coord,
-    radius: usize,
+/// A sensor and the closest beacon it detected.
+pub type Reading = (Sensor, Point<i32>);
+
+pub struct Sensor {
+    pub coord: Point<i32>,
+    pub radius: usize,
 }
 
 impl Sensor {
-    fn range_for_y(&self, y: &i32) -> Range<i32> {
-        let y_diff = self.coord.y.abs_diff(*y) as i32;
+    pub fn range_for_y(&self, y: &i32) -> Range<i32> {
+        let y_diff = self.coord.y.abs_diff(*y) as usize;
 
-        if y_diff > self.radius as i32 {
+        if y_diff > self.radius {
             return 0..0; // Empty range
         }
 
-        let x_diff = self.radius as i32 - y_diff;
+        let x_diff = (self.radius - y_diff) as i32;
         (self.coord.x - x_diff)..(self.coord.x + x_diff + 1)
     }
 }
 
-fn parse_line(line: &str) -> (Sensor, Coord) {
-    let template =
-        Regex::new(r"Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)")
-            .unwrap();
-    let captures = template.captures(line).unwrap();
-
-    let sensor = Coord {
-        x: captures[1].parse::<i32>().unwrap(),
-        y: captures[2].parse::<i32>().unwrap(),
-    };
-
-    let beacon = Coord {
-        x: captures[3].parse::<i32>().unwrap(),
-        y: captures[4].parse::<i32>().unwrap(),
-    };
-
-    let diff_x = sensor.x.abs_diff(beacon.x);
-    let diff_y = sensor.y.abs_diff(beacon.y);
-
-    (
+pub fn parse_line(line: &str) -> Result<Reading, ParseError> {
+    let (sensor, beacon) = complete(line, |cursor| {
+        Ok((
+            cursor.field("Sensor at ", parse_point)?,
+            cursor.field(": closest beacon is at ", parse_point)?,
+        ))
+    })?;
+
+    // The ranges a sensor covers end one past its reach, which has to fit in an i32 too
+    let radius = sensor
+        .checked_manhattan(beacon)
+        .filter(|radius| {
+            let reach = radius.checked_add(1);
+            reach.and_then(|reach| sensor.x.checked_add(reach)).is_some()
+                && sensor.x.checked_sub(*radius).is_some()
+        })
+        .ok_or_else(|| ParseError::new("the sensor is too far from its beacon"))?;
+
+    Ok((
         Sensor {
             coord: sensor,
-            radius: (diff_x + diff_y) as usize,
+            radius: radius as usize,
         },
         beacon,
-    )
+    ))
 }
 
-fn range_union<N>(r1: &Range<N>, r2: &Range<N>) -> Option<Range<N>>
-where
-    N: Copy + Ord + PartialOrd,
-{
-    if r1.start <= r2.start && r1.end > r2.start {
-        return Some(r1.start..r1.end.max(r2.end));
-    }
-
-    if r2.start <= r1.start && r2.end > r1.start {
-        return Some(r2.start..r1.end.max(r2.end));
-    }
-
-    None
+fn parse_point(cursor: &mut Cursor) -> Result<Point<i32>, ParseError> {
+    Ok(Point::new(
+        cursor.field("x=", Cursor::signed)?,
+        cursor.field(", y=", Cursor::signed)?,
+    ))
 }
 
-fn merge_ranges<N>(ranges: &[Range<N>]) -> (Vec<Range<N>>, bool)
-where
-    N: Copy + Debug + Ord + PartialOrd,
-{
-    let mut merged_at_least_once = false;
-    let mut merged_ranges = vec![];
-    let mut processed_ranges = vec![];
+/// The only position within `0..=max` on both axes no sensor covers.
+fn distress_beacon(readings: &[Reading], max: i32) -> Result<Option<Point<i32>>, SolveError> {
+    for y in 0..=max {
+        cancel::check()?;
 
-    for (index, r1) in ranges.iter().enumerate() {
-        let inner_start = index + 1;
-        let mut merged = false;
+        let sensors = readings.iter().map(|(sensor, _)| sensor);
+        let uncovered = ranges_for_y(sensors, &y).complement(0..max + 1);
 
-        if inner_start >= ranges.len() {
-            if !processed_ranges.contains(&index) {
-                merged_ranges.push(r1.clone());
-            }
-            break;
-        }
-
-        if processed_ranges.contains(&index) {
-            continue;
+        if let Some(range) = uncovered.ranges().first() {
+            return Ok(Some(Point::new(range.start, y)));
         }
+    }
 
-        for (inner_index, r2) in ranges[inner_start..].iter().enumerate() {
-            let inner_index = inner_index + inner_start;
+    Ok(None)
+}
 
-            if processed_ranges.contains(&inner_index) {
-                continue;
-            }
+pub fn ranges_for_y<'a, I>(sensors: I, y: &i32) -> IntervalSet<i32>
+where
+    I: IntoIterator<Item = &'a Sensor>,
+{
+    sensors.into_iter().map(|sensor| sensor.range_for_y(y)).collect()
+}
 
-            if let Some(range) = range_union(r1, r2) {
-                merged_ranges.push(range);
-                processed_ranges.push(index);
-                processed_ranges.push(inner_index);
-                merged = true;
-                merged_at_least_once = true;
-                break;
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap(), &Params { y: 10, max: 20 }), Ok(26));
+    }
 
-        if !merged {
-            merged_ranges.push(r1.clone());
-        }
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap(), &Params { y: 10, max: 20 }), Ok(56000011));
     }
 
-    (merged_ranges, merged_at_least_once)
-}
+    #[test]
+    fn rejects_beacons_out_of_reach() {
+        let result = parse("Sensor at x=-2000000000, y=0: closest beacon is at x=2000000000, y=0");
 
-fn ranges_for_y(sensors: &[Sensor], y: &i32) -> Vec<Range<i32>> {
-    let mut ranges = sensors
-        .iter()
-        .map(|sensor| sensor.range_for_y(y))
-        .filter(|range| !range.is_empty())
-        .collect_vec();
+        assert!(matches!(
+            result,
+            Err(err) if err.to_string().starts_with("line 1: the sensor is too far from its beacon")
+        ));
+    }
 
-    loop {
-        let (merged, merged_at_least_once) = merge_ranges(&ranges);
-        ranges = merged;
+    #[test]
+    fn renders_the_coverage() {
+        let image = render(&parse(EXAMPLE).unwrap(), &Params { y: 10, max: 20 }).unwrap();
 
-        if !merged_at_least_once {
-            break;
-        }
+        assert_eq!((image.width(), image.height()), (399, 399));
+        assert_eq!(image.get(Point::new(14, 11)), Some(Rgb::WHITE));
+        assert_eq!(image.get(Point::new(8, 7)), Some(Rgb(224, 48, 32)));
+        assert_eq!(image.get(Point::new(2, 10)), Some(Rgb(240, 208, 64)));
+        assert_eq!(image.get(Point::new(10, 10)), Some(Rgb(40, 72, 120)));
     }
-
-    ranges
 }
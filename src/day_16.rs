@@ -1,330 +1,243 @@
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::HashMap};
 
 use itertools::Itertools;
-use regex::Regex;
 
-use crate::utils::read_lines;
+use crate::cancel;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Answer, Puzzle};
+use crate::trace::debug;
+use crate::utils::search::{bfs_distances, Graph};
+use crate::utils::parse::{complete, Cursor};
+use crate::utils::parse_lines;
 
-pub fn part_1() -> usize {
-    let input = r"Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
-Valve BB has flow rate=13; tunnels lead to valves CC, AA
-Valve CC has flow rate=2; tunnels lead to valves DD, BB
-Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
-Valve EE has flow rate=3; tunnels lead to valves FF, DD
-Valve FF has flow rate=0; tunnels lead to valves EE, GG
-Valve GG has flow rate=0; tunnels lead to valves FF, HH
-Valve HH has flow rate=22; tunnel leads to valve GG
-Valve II has flow rate=0; tunnels lead to valves AA, JJ
-Valve JJ has flow rate=21; tunnel leads to valve II";
-    let mut valves =
-        input
-            .lines()
-            .map(|line| parse_line(line))
-            .fold(HashMap::new(), |mut sum, valve| {
-                sum.insert(valve.name.clone(), valve);
-                sum
-            });
-
-    let valves_clone = valves.clone();
-
-    let useless = valves_clone
-        .values()
-        .filter(|v| v.links.len() == 2 && v.rate == 0)
-        .collect_vec();
+pub struct Day;
 
-    for v in useless {
-        let tmp = valves.get(&v.name).unwrap().clone();
+impl Puzzle for Day {
+    const DAY: usize = 16;
+    const TITLE: &'static str = "Proboscidea Volcanium";
 
-        valves = optimize(valves, &tmp.links[0].to, &v.name, &tmp.links[1]);
-        valves = optimize(valves, &tmp.links[1].to, &v.name, &tmp.links[0]);
+    type Parsed = Volcano;
+    type Params = ();
 
-        valves.remove(&v.name);
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
     }
 
-    for v in &valves {
-        println!("{:?}", v);
+    fn part_1(volcano: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_1(volcano).map(Answer::from)
     }
 
-    let tmp = find(&vec![], &valves, 30, "AA", &[], &[]);
-
-    println!("{:?}", tmp);
-
-    calculate_flow(30, &tmp.unwrap().1, true);
-
-    0
+    fn part_2(volcano: &Self::Parsed, _: &()) -> Result<Answer, SolveError> {
+        part_2(volcano).map(Answer::from)
+    }
 }
 
-type Valves = HashMap<String, Valve>;
+const START: &str = "AA";
 
-#[derive(Clone)]
-enum Action {
-    Move(Link),
-    Open(Valve),
+pub fn part_1(volcano: &Volcano) -> Result<usize, SolveError> {
+    Ok(volcano.best_releases(30)?.into_values().max().unwrap_or(0))
 }
 
-impl Action {
-    fn name(&self) -> String {
-        match self {
-            Action::Move(link) => link.to.clone(),
-            Action::Open(valve) => valve.name.clone(),
-        }
-    }
+pub fn part_2(volcano: &Volcano) -> Result<usize, SolveError> {
+    let releases = volcano
+        .best_releases(26)?
+        .into_iter()
+        .sorted_by_key(|(_, pressure)| Reverse(*pressure))
+        .collect_vec();
 
-    fn minutes(&self) -> usize {
-        match self {
-            Action::Move(link) => link.length,
-            Action::Open(_) => 1,
-        }
-    }
+    let mut best = 0;
 
-    fn to_string(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Action::Move(link) => write!(f, "Move({}, {})", link.to, link.length),
-            Action::Open(valve) => write!(f, "Open({}, {})", valve.name, valve.rate),
+    // With the releases sorted, the elephant's share can only shrink from here on
+    for (index, (mine, my_pressure)) in releases.iter().enumerate() {
+        if my_pressure * 2 < best {
+            break;
         }
-    }
-}
-
-impl std::fmt::Display for Action {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.to_string(f)
-    }
-}
 
-impl std::fmt::Debug for Action {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.to_string(f)
+        if let Some((_, elephant_pressure)) = releases[index..]
+            .iter()
+            .find(|(elephant, _)| mine & elephant == 0)
+        {
+            best = best.max(my_pressure + elephant_pressure);
+        }
     }
-}
 
-#[derive(Clone)]
-struct Link {
-    to: String,
-    length: usize,
+    Ok(best)
 }
 
-impl std::fmt::Debug for Link {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Link({}, {})", self.to, self.length)
-    }
+pub fn parse(input: &str) -> Result<Volcano, ParseError> {
+    Volcano::new(&parse_lines(input, parse_line)?)
 }
 
-#[derive(Clone)]
 struct Valve {
     name: String,
     rate: usize,
-    links: Vec<Link>,
-}
-
-impl std::fmt::Debug for Valve {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Valve({}, {}, {:?})", self.name, self.rate, self.links)
-    }
+    tunnels: Vec<String>,
 }
 
-fn optimize(mut valves: Valves, node: &str, from: &str, to: &Link) -> Valves {
-    // println!("Reconnect: {node} from {from} to {}", to.to);
-    let mut link = valves
-        .get_mut(node)
-        .unwrap()
-        .links
-        .iter_mut()
-        .find(|v| v.to == from)
-        .unwrap();
-    link.to = to.to.clone();
-    link.length += to.length;
-
-    valves
+struct Tunnels<'a> {
+    valves: &'a [Valve],
+    indexes: HashMap<&'a str, usize>,
 }
 
-fn go(
-    valves: Valves,
-    from_name: &str,
-    visited: &[String],
-    opened: &[String],
-) -> Vec<(Vec<Action>, Valves, Vec<String>, Vec<String>)> {
-    let mut updated_valves = valves.clone();
-    let Some(from) = updated_valves.remove(from_name) else {
-    // println!("No key: {from_name} in {:?}", valves.keys());
-        // println!("{:?}", valves.keys());
-        return vec![];
-    };
-    let mut output = vec![];
-
-    // println!("");
-
-    for (i1, l1) in from.links.iter().enumerate() {
-        if let Some(v) = updated_valves.get_mut(&l1.to) {
-            v.links.retain(|l| l.to != from_name);
-        }
-
-        for (i2, l2) in from.links.iter().enumerate() {
-            if i1 == i2 {
-                continue;
-            }
+impl Graph for Tunnels<'_> {
+    type Node = usize;
 
-            let new_length = l1.length + l2.length;
-            if let Some(v) = updated_valves.get_mut(&l1.to) {
-                if let Some(link) = v.links.iter_mut().find(|l| l.to == l2.to) {
-                    link.length = link.length.min(new_length);
-                } else {
-                    v.links.push(Link {
-                        to: l2.to.clone(),
-                        length: new_length,
-                    });
-                }
-            }
-        }
+    fn neighbours(&self, valve: &usize) -> impl Iterator<Item = usize> {
+        self.valves[*valve]
+            .tunnels
+            .iter()
+            .map(|name| self.indexes[name.as_str()])
     }
+}
 
-    // println!("For {from_name} ppdated: {:?}", updated_valves);
-
-    for link in &from.links {
-        // println!("  Link to {:?}", link);
-
-        let valve = valves.get(&link.to).unwrap();
+/// The valves worth opening, with the travel time between any two of them.
+pub struct Volcano {
+    rates: Vec<usize>,
+    /// Row and column `rates.len()` hold the distances from the start.
+    distances: Vec<Vec<Option<usize>>>,
+}
 
-        if !opened.contains(&valve.name) {
-            let mut actions = vec![];
-            actions.push(Action::Move(link.clone()));
-            let mut opened = opened.to_vec();
-            if valve.rate > 0 {
-                actions.push(Action::Open(valve.clone()));
-                opened.push(valve.name.to_owned());
+impl Volcano {
+    fn new(valves: &[Valve]) -> Result<Self, ParseError> {
+        let indexes: HashMap<&str, usize> = valves
+            .iter()
+            .enumerate()
+            .map(|(index, valve)| (valve.name.as_str(), index))
+            .collect();
+
+        for valve in valves {
+            let unknown = valve
+                .tunnels
+                .iter()
+                .find(|name| !indexes.contains_key(name.as_str()));
+
+            if let Some(unknown) = unknown {
+                return Err(ParseError::new(format!(
+                    "valve {} leads to the unknown valve {unknown}",
+                    valve.name
+                )));
             }
-            output.push((actions, updated_valves.clone(), vec![], opened));
         }
 
-        // TODO : improve it to not go same way twice
-        if !visited.contains(&link.to) {
-            let mut visited = visited.to_vec();
-            visited.push(from_name.to_owned());
-            output.push((
-                vec![Action::Move(link.clone())],
-                valves.clone(),
-                visited,
-                opened.to_vec(),
-            ));
-        }
-    }
+        let start = *indexes
+            .get(START)
+            .ok_or_else(|| ParseError::new(format!("there is no valve {START}")))?;
 
-    output
-}
+        let useful = (0..valves.len())
+            .filter(|index| valves[*index].rate > 0)
+            .collect_vec();
 
-fn find(
-    path: &[Action],
-    valves: &Valves,
-    minutes_left: usize,
-    from_name: &str,
-    visited: &[String],
-    opened: &[String],
-) -> Option<(usize, Vec<Action>)> {
-    if minutes_left <= 1 || valves.len() <= 1 {
-        if path.is_empty() {
-            // println!("No path detected");
-            return None;
+        if useful.len() > u64::BITS as usize {
+            return Err(ParseError::new(format!(
+                "at most {} valves can have a flow rate, got {}",
+                u64::BITS,
+                useful.len()
+            )));
         }
 
-        // println!("Path is too long: {:?}", path);
-        let calc = calculate_flow(30, path, false);
-        // println!(
-        //     "{calc}, {:?}",
-        //     path.iter()
-        //         .filter(|l| match l {
-        //             Action::Open(_) => true,
-        //             _ => false,
-        //         })
-        //         .collect_vec()
-        // );
-        return Some((calc, path.to_vec()));
-    }
-
-    go(valves.clone(), from_name, &visited, &opened)
-        .into_iter()
-        .filter(|(actions, _, _, _)| take_minutes(actions) < minutes_left)
-        .filter_map(|(actions, valves, visited, opened)| {
-            let mut path = path.clone().to_vec();
-            path.extend(actions.clone());
-            find(
-                &path,
-                &valves,
-                minutes_left - take_minutes(&actions) - 1,
-                &actions.last().unwrap().name(),
-                &visited,
-                &opened,
-            )
+        debug!("{} of {} valves have a flow rate", useful.len(), valves.len());
+
+        let tunnels = Tunnels { valves, indexes };
+        let distances = useful
+            .iter()
+            .chain([&start])
+            .map(|from| {
+                let reachable = bfs_distances(&tunnels, [*from]);
+
+                useful
+                    .iter()
+                    .chain([&start])
+                    .map(|to| reachable.get(to).copied())
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        Ok(Self {
+            rates: useful.iter().map(|index| valves[*index].rate).collect(),
+            distances,
         })
-        .max_by(|(a, _), (b, _)| a.cmp(b))
-}
-
-fn take_minutes(actions: &[Action]) -> usize {
-    actions.iter().map(|a| a.minutes()).sum()
-}
+    }
 
-fn calculate_flow(minutes: usize, actions: &[Action], debug: bool) -> usize {
-    let mut add = 0;
-    let mut output = 0;
+    /// The most pressure that can be released by opening exactly the valves in each reachable set.
+    fn best_releases(&self, minutes: usize) -> Result<HashMap<u64, usize>, SolveError> {
+        let mut best = HashMap::new();
+        self.explore(self.rates.len(), minutes, 0, 0, &mut best)?;
+        debug!("{} sets of valves can be opened in {minutes} minutes", best.len());
 
-    if debug {
-        let path = actions.iter().map(|a| format!("{a}")).join(" => ");
-        println!("\nActions: {:?}", path);
+        Ok(best)
     }
 
-    let mut left_minutes = minutes;
-    for action in actions {
-        if debug {
-            println!("{action}");
-        }
-
-        if left_minutes < action.minutes() {
-            break;
-        }
+    fn explore(
+        &self,
+        valve: usize,
+        minutes: usize,
+        opened: u64,
+        pressure: usize,
+        best: &mut HashMap<u64, usize>,
+    ) -> Result<(), SolveError> {
+        cancel::check()?;
 
-        left_minutes -= action.minutes();
+        let known = best.entry(opened).or_insert(0);
+        *known = pressure.max(*known);
 
-        match action {
-            Action::Move(_) => {
-                output += add * action.minutes();
-            }
-            Action::Open(valve) => {
-                output += add;
-                add += valve.rate;
+        for (next, rate) in self.rates.iter().enumerate() {
+            if opened & (1 << next) != 0 {
+                continue;
             }
-        }
 
-        if debug {
-            println!("  After:   minutes left: {left_minutes},  add: {add},  output: {output}",);
-        }
-    }
+            // Walking there and then a minute to open the valve
+            let Some(cost) = self.distances[valve][next].map(|distance| distance + 1) else {
+                continue;
+            };
 
-    if left_minutes > 0 {
-        output += add * left_minutes;
-        left_minutes = 0;
-    }
+            if cost < minutes {
+                let left = minutes - cost;
+                self.explore(next, left, opened | (1 << next), pressure + left * rate, best)?;
+            }
+        }
 
-    if debug {
-        println!("  End:     minutes left: {left_minutes},  add: {add},  output: {output}");
+        Ok(())
     }
+}
 
-    output
+fn parse_line(line: &str) -> Result<Valve, ParseError> {
+    complete(line, |cursor| {
+        let name = cursor.field("Valve ", Cursor::word)?.to_owned();
+        let rate = cursor.field(" has flow rate=", Cursor::unsigned)?;
+        cursor.one_of(&["; tunnels lead to valves ", "; tunnel leads to valve "])?;
+        let tunnels = cursor.separated(", ", |cursor| Ok(cursor.word()?.to_owned()))?;
+
+        Ok(Valve {
+            name,
+            rate,
+            tunnels,
+        })
+    })
 }
 
-fn parse_line(line: &str) -> Valve {
-    let template =
-        Regex::new(r"^Valve (\w+) has flow rate=(\d+); tunnels? leads? to valves? (.+)$").unwrap();
-    let captures = template.captures(line).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let leads_to = captures[3]
-        .split(", ")
-        .map(|valve| Link {
-            to: valve.to_owned(),
-            length: 1,
-        })
-        .collect_vec();
+    const EXAMPLE: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II";
+
+    #[test]
+    fn part_1_example() {
+        assert_eq!(part_1(&parse(EXAMPLE).unwrap()), Ok(1651));
+    }
 
-    Valve {
-        links: leads_to,
-        name: captures[1].to_owned(),
-        rate: captures[2].parse::<usize>().unwrap(),
+    #[test]
+    fn part_2_example() {
+        assert_eq!(part_2(&parse(EXAMPLE).unwrap()), Ok(1707));
     }
 }
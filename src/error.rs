@@ -0,0 +1,201 @@
+use std::{fmt, time::Duration};
+
+use crate::params::ParamError;
+use crate::timing::format_duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    message: String,
+    day: Option<usize>,
+    record: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    text: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            day: None,
+            record: None,
+            line: None,
+            column: None,
+            text: None,
+        }
+    }
+
+    /// Points the error at `part`, which has to be a slice of `text`.
+    pub fn at(self, text: &str, part: &str) -> Self {
+        let offset = (part.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|offset| *offset <= text.len())
+            .unwrap_or(0);
+
+        self.at_column(text[..offset].chars().count() + 1)
+    }
+
+    /// Points the error at `part`, a slice of the multi-line `text` starting at `first_line`.
+    pub fn at_within(self, text: &str, part: &str, first_line: usize) -> Self {
+        let offset = (part.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|offset| *offset <= text.len())
+            .unwrap_or(0);
+        let line_start = text[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = text[offset..]
+            .find('\n')
+            .map_or(text.len(), |index| offset + index);
+        let line = &text[line_start..line_end];
+
+        self.at(line, &text[offset..line_end])
+            .in_line(first_line + text[..line_start].matches('\n').count(), line)
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column.get_or_insert(column);
+        self
+    }
+
+    pub fn in_line(mut self, line: usize, text: &str) -> Self {
+        if self.line.is_none() {
+            self.line = Some(line);
+            self.text = Some(text.to_owned());
+        }
+        self
+    }
+
+    /// Names the blank-line separated record the error is in, see [`crate::utils::records`].
+    pub fn in_record(mut self, record: usize) -> Self {
+        self.record.get_or_insert(record);
+        self
+    }
+
+    pub fn in_day(mut self, day: usize) -> Self {
+        self.day.get_or_insert(day);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = [
+            self.day.map(|day| format!("day {day:02}")),
+            self.record.map(|record| format!("record {record}")),
+            self.line.map(|line| format!("line {line}")),
+            self.column.map(|column| format!("column {column}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+
+        write!(f, "{}", self.message)?;
+
+        if let (Some(line), Some(text)) = (self.line, &self.text) {
+            let gutter = " ".repeat(line.to_string().len());
+
+            write!(f, "\n {gutter} |\n {line} | {text}")?;
+
+            if let Some(column) = self.column {
+                write!(f, "\n {gutter} | {}^", " ".repeat(column - 1))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why a part has no answer: its input does not read as expected, or solving it went wrong.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    /// A parameter override the day cannot use.
+    Param(ParamError),
+    /// The input reads fine, but the puzzle has no answer for it.
+    NoAnswer(String),
+    /// The input of the day cannot be read.
+    Input(String),
+    /// The part was stopped before it found its answer.
+    Cancelled,
+    /// The part was still running after the time it was allowed, see [`crate::cancel`].
+    TimedOut(Duration),
+    /// The solver panicked, with the panic message.
+    Panicked(String),
+}
+
+impl SolveError {
+    pub fn no_answer(message: impl Into<String>) -> Self {
+        Self::NoAnswer(message.into())
+    }
+
+    /// The error as reported for `day`, with the input location of a parse error.
+    pub fn describe(&self, day: usize) -> String {
+        match self {
+            Self::Parse(err) => err.clone().in_day(day).to_string(),
+            err => format!("day {day:02}: {err}"),
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ParamError> for SolveError {
+    fn from(err: ParamError) -> Self {
+        Self::Param(err)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::Param(err) => write!(f, "{err}"),
+            Self::NoAnswer(message) | Self::Input(message) => write!(f, "{message}"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::TimedOut(limit) => write!(f, "timed out after {}", format_duration(*limit)),
+            Self::Panicked(message) => write!(f, "panicked: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_the_offending_text() {
+        let line = "addx five";
+        let err = ParseError::new("expected a number")
+            .at(line, &line[5..])
+            .in_line(12, line)
+            .in_day(10);
+
+        assert_eq!(
+            err.to_string(),
+            "day 10, line 12, column 6: expected a number\n    |\n 12 | addx five\n    |      ^"
+        );
+    }
+
+    #[test]
+    fn describes_solve_errors_in_their_day() {
+        assert_eq!(
+            SolveError::from(ParseError::new("expected a number").in_line(3, "x")).describe(7),
+            "day 07, line 3: expected a number\n   |\n 3 | x"
+        );
+        assert_eq!(
+            SolveError::no_answer("the end cannot be reached").describe(12),
+            "day 12: the end cannot be reached"
+        );
+    }
+}
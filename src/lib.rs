@@ -0,0 +1,61 @@
+pub mod animation;
+pub mod answers;
+pub mod cancel;
+pub mod cli;
+pub mod error;
+pub mod params;
+pub mod pool;
+pub mod report;
+pub mod runner;
+pub mod simulation;
+pub mod solution;
+pub mod timing;
+pub mod trace;
+pub mod utils;
+
+use solution::Solution;
+
+macro_rules! days {
+    ($($day:ident),* $(,)?) => {
+        $(pub mod $day;)*
+
+        /// Every solved day, in order.
+        pub fn registry() -> Vec<&'static dyn Solution> {
+            vec![$(&$day::Day),*]
+        }
+    };
+}
+
+days!(
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16,
+);
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn registry_covers_every_day_module() {
+        let days = registry()
+            .iter()
+            .map(|solution| solution.day())
+            .collect::<Vec<_>>();
+
+        let mut modules = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src"))
+            .unwrap()
+            .filter_map(|entry| {
+                let name = entry.unwrap().file_name().into_string().unwrap();
+                name.strip_prefix("day_")?
+                    .strip_suffix(".rs")?
+                    .parse::<usize>()
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        modules.sort();
+
+        assert_eq!(days, modules);
+    }
+}
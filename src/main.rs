@@ -1,44 +1,109 @@
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_14;
-mod day_15;
-mod utils;
+use std::{env, process};
+
+use aoc_2022_rust::answers::{Answers, Verdict};
+use aoc_2022_rust::cli::{self, Command};
+use aoc_2022_rust::error::SolveError;
+use aoc_2022_rust::registry;
+use aoc_2022_rust::runner::{bench, solve};
+use aoc_2022_rust::trace;
 
 fn main() {
-    println!("01 / 01: {}", day_01::part_1());
-    println!("01 / 02: {}", day_01::part_2());
-    println!("02 / 01: {}", day_02::part_1());
-    println!("02 / 02: {}", day_02::part_2());
-    println!("03 / 01: {}", day_03::part_1());
-    println!("03 / 02: {}", day_03::part_2());
-    println!("04 / 01: {}", day_04::part_1());
-    println!("04 / 02: {}", day_04::part_2());
-    println!("05 / 01: {}", day_05::part_1());
-    println!("05 / 02: {}", day_05::part_2());
-    println!("06 / 01: {}", day_06::part_1());
-    println!("06 / 02: {}", day_06::part_2());
-    println!("07 / 01: {}", day_07::part_1());
-    println!("07 / 02: {}", day_07::part_2());
-    println!("08 / 01: {}", day_08::part_1());
-    println!("08 / 02: {}", day_08::part_2());
-    println!("09 / 01: {}", day_09::part_1());
-    println!("09 / 02: {}", day_09::part_2());
-    println!("10 / 01: {}", day_10::part_1());
-    println!("10 / 02:");
-    day_10::part_2();
-    println!("11 / 01: {}", day_11::part_1());
-    println!("11 / 02: {}", day_11::part_2());
-    println!("12 / 01: {}", day_12::part_1());
-    println!("12 / 02: {}", day_12::part_2());
-    println!("15 / 01: {}", day_15::part_1());
+    let registry = registry();
+    let available_days = registry
+        .iter()
+        .map(|solution| solution.day())
+        .collect::<Vec<_>>();
+
+    let command = match cli::parse(env::args().skip(1), &available_days) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {err}\n\nRun with --help to see usage.");
+            process::exit(2);
+        }
+    };
+
+    if let Command::Run(args) | Command::Verify(args, _) | Command::Bench(args, _) = &command {
+        trace::init(args.log.clone(), args.verbosity);
+    }
+
+    match command {
+        Command::Help => println!("{}", cli::USAGE),
+        Command::List => {
+            for solution in &registry {
+                println!("{:02}: {}", solution.day(), solution.title());
+            }
+        }
+        Command::Run(args) => {
+            let succeeded = solve(&registry, &args, |report| {
+                report.print(args.format, args.time)
+            });
+
+            if !succeeded {
+                process::exit(1);
+            }
+        }
+        Command::Verify(args, path) => {
+            let answers = match Answers::load(&path) {
+                Ok(answers) => answers,
+                Err(err) => {
+                    eprintln!("error: answers file: {err}");
+                    process::exit(2);
+                }
+            };
+
+            let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+
+            let succeeded = solve(&registry, &args, |report| {
+                let label = report.label();
+                let answer = match &report.answer {
+                    Ok(answer) => answer,
+                    Err(SolveError::TimedOut(_)) => {
+                        failed += 1;
+                        println!("{label}: TIMEOUT");
+                        return;
+                    }
+                    Err(err) => {
+                        failed += 1;
+                        println!("{label}: ERROR");
+                        eprintln!("error: {}", err.describe(report.day));
+                        return;
+                    }
+                };
+
+                match answers.check(report.day, report.part, answer) {
+                    Verdict::Pass => {
+                        passed += 1;
+                        println!("{label}: PASS");
+                    }
+                    Verdict::Fail(expected) => {
+                        failed += 1;
+                        println!("{label}: FAIL");
+                        println!(
+                            "    expected: {}",
+                            expected.to_string().replace('\n', "\n              ")
+                        );
+                        println!(
+                            "    got:      {}",
+                            answer.to_string().replace('\n', "\n              ")
+                        );
+                    }
+                    Verdict::Unknown => {
+                        unknown += 1;
+                        println!("{label}: UNKNOWN ({answer})");
+                    }
+                }
+            });
+
+            println!("\n{passed} passed, {failed} failed, {unknown} unknown");
+
+            if !succeeded || failed > 0 {
+                process::exit(1);
+            }
+        }
+        Command::Bench(args, runs) => {
+            if !bench(&registry, &args, runs) {
+                process::exit(1);
+            }
+        }
+    }
 }
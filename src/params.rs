@@ -0,0 +1,160 @@
+use std::{fmt, str::FromStr};
+
+use itertools::Itertools;
+
+/// Parameter overrides as given on the command line, e.g. `--param y=10`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overrides(Vec<(String, String)>);
+
+impl Overrides {
+    pub fn push(&mut self, key: &str, value: &str) {
+        self.0.push((key.to_owned(), value.to_owned()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks every override names one of `keys`, before anything is run.
+    pub fn check(&self, keys: &'static [&'static str]) -> Result<(), ParamError> {
+        match self.0.iter().find(|(key, _)| !keys.contains(&key.as_str())) {
+            Some((key, _)) => Err(ParamError::Unknown(key.clone(), keys)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A typed set of puzzle parameters, declared with [`params!`].
+pub trait Params: Default {
+    const KEYS: &'static [&'static str];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError>;
+
+    fn resolve(overrides: &Overrides) -> Result<Self, ParamError> {
+        let mut params = Self::default();
+
+        for (key, value) in &overrides.0 {
+            params.set(key, value)?;
+        }
+
+        Ok(params)
+    }
+}
+
+/// For the days without parameters.
+impl Params for () {
+    const KEYS: &'static [&'static str] = &[];
+
+    fn set(&mut self, key: &str, _value: &str) -> Result<(), ParamError> {
+        Err(ParamError::Unknown(key.to_owned(), Self::KEYS))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamError {
+    Unknown(String, &'static [&'static str]),
+    Invalid(String, String),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(key, []) => write!(f, "unknown parameter '{key}', this day has none"),
+            Self::Unknown(key, keys) => write!(
+                f,
+                "unknown parameter '{key}', expected one of: {}",
+                keys.iter().join(", ")
+            ),
+            Self::Invalid(key, value) => write!(f, "invalid value '{value}' for parameter '{key}'"),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+pub fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, ParamError> {
+    value
+        .parse()
+        .map_err(|_| ParamError::Invalid(key.to_owned(), value.to_owned()))
+}
+
+/// Declares a day's parameter struct with the default of every field:
+///
+/// ```ignore
+/// params! {
+///     pub struct Params {
+///         pub y: i32 = 2_000_000,
+///     }
+/// }
+/// ```
+macro_rules! params {
+    (
+        pub struct $name:ident {
+            $(pub $field:ident: $type:ty = $default:expr,)*
+        }
+    ) => {
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name {
+            $(pub $field: $type,)*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl $crate::params::Params for $name {
+            const KEYS: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            fn set(&mut self, key: &str, value: &str) -> Result<(), $crate::params::ParamError> {
+                match key {
+                    $(stringify!($field) => self.$field = $crate::params::parse_value(key, value)?,)*
+                    _ => return Err($crate::params::ParamError::Unknown(key.to_owned(), Self::KEYS)),
+                }
+
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use params;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    params! {
+        pub struct Example {
+            pub rounds: u32 = 20,
+            pub name: String = "abc".to_owned(),
+        }
+    }
+
+    #[test]
+    fn resolves_overrides_over_defaults() {
+        let mut overrides = Overrides::default();
+        overrides.push("rounds", "5");
+
+        assert_eq!(
+            Example::resolve(&overrides),
+            Ok(Example {
+                rounds: 5,
+                name: "abc".to_owned()
+            })
+        );
+
+        overrides.push("rounds", "many");
+        assert_eq!(
+            Example::resolve(&overrides).unwrap_err().to_string(),
+            "invalid value 'many' for parameter 'rounds'"
+        );
+        assert_eq!(
+            overrides.check(&[]).unwrap_err().to_string(),
+            "unknown parameter 'rounds', this day has none"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+/// The number of jobs run at once when none is asked for: one per available core.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Runs `task` on every item with up to `jobs` threads, handing the results to `on_result`
+/// in the order of the items, each as soon as it and all the ones before it are done.
+pub fn run_in_order<T, R, F, G>(items: Vec<T>, jobs: usize, task: F, mut on_result: G)
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    G: FnMut(R),
+{
+    let jobs = jobs.min(items.len());
+
+    if jobs <= 1 {
+        items.into_iter().map(task).for_each(on_result);
+        return;
+    }
+
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (queue, task) = (&queue, &task);
+
+            scope.spawn(move || loop {
+                // The lock is released before the task runs, so other workers can pick theirs
+                let Some((index, item)) = queue.lock().unwrap().next() else {
+                    break;
+                };
+
+                if sender.send((index, task(item))).is_err() {
+                    break;
+                }
+            });
+        }
+
+        drop(sender);
+
+        let mut pending = HashMap::new();
+        let mut next = 0;
+
+        for (index, result) in receiver {
+            pending.insert(index, result);
+
+            while let Some(result) = pending.remove(&next) {
+                on_result(result);
+                next += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn hands_results_back_in_order() {
+        for jobs in [1, 3, 8] {
+            let mut results = vec![];
+
+            run_in_order(
+                vec![30, 0, 20, 5, 10],
+                jobs,
+                |millis| {
+                    thread::sleep(Duration::from_millis(millis));
+                    millis * 2
+                },
+                |result| results.push(result),
+            );
+
+            assert_eq!(results, [60, 0, 40, 10, 20]);
+        }
+    }
+}
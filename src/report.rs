@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use crate::error::SolveError;
+use crate::solution::Answer;
+use crate::timing::format_duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Result<Answer, SolveError>,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn label(&self) -> String {
+        format!("{:02} / {:02}", self.day, self.part)
+    }
+
+    pub fn status(&self) -> &'static str {
+        match &self.answer {
+            Ok(_) => "solved",
+            Err(SolveError::TimedOut(_)) => "timeout",
+            Err(_) => "error",
+        }
+    }
+
+    /// Prints the report to stdout, or its error to stderr.
+    pub fn print(&self, format: Format, time: bool) {
+        let elapsed = if time {
+            format!(" ({})", format_duration(self.elapsed))
+        } else {
+            String::new()
+        };
+
+        match (format, &self.answer) {
+            (Format::Json, _) => println!("{}", self.to_json()),
+            (Format::Text, Err(SolveError::TimedOut(_))) => {
+                println!("{}: TIMEOUT{elapsed}", self.label())
+            }
+            (Format::Text, Err(err)) => eprintln!("error: {}", err.describe(self.day)),
+            (Format::Text, Ok(answer)) => {
+                if answer.is_multiline() {
+                    println!("{}{elapsed}:\n{answer}", self.label());
+                } else {
+                    println!("{}: {answer}{elapsed}", self.label());
+                }
+            }
+        }
+    }
+
+    /// A single line JSON object, so a run can be consumed as JSON Lines.
+    pub fn to_json(&self) -> String {
+        let (answer, kind, error) = match &self.answer {
+            Ok(Answer::Number(number)) => (number.to_string(), "\"number\"", "null".to_owned()),
+            Ok(Answer::Text(text)) if text.contains('\n') => {
+                (json_string(text), "\"multiline\"", "null".to_owned())
+            }
+            Ok(Answer::Text(text)) => (json_string(text), "\"string\"", "null".to_owned()),
+            Err(err) => (
+                "null".to_owned(),
+                "null",
+                json_string(&err.describe(self.day)),
+            ),
+        };
+
+        format!(
+            "{{\"day\":{},\"part\":{},\"status\":\"{}\",\"answer\":{answer},\"type\":{kind},\"duration_ms\":{:.3},\"error\":{error}}}",
+            self.day,
+            self.part,
+            self.status(),
+            self.elapsed.as_secs_f64() * 1000.0,
+        )
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut output = String::from('"');
+
+    for char in value.chars() {
+        match char {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            char if char.is_control() => output.push_str(&format!("\\u{:04x}", char as u32)),
+            char => output.push(char),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+
+    fn report(answer: Result<Answer, SolveError>) -> Report {
+        Report {
+            day: 10,
+            part: 2,
+            answer,
+            elapsed: Duration::from_micros(1_500),
+        }
+    }
+
+    #[test]
+    fn types_answers_in_json() {
+        assert_eq!(
+            report(Ok(Answer::Number(42))).to_json(),
+            r##"{"day":10,"part":2,"status":"solved","answer":42,"type":"number","duration_ms":1.500,"error":null}"##
+        );
+        assert_eq!(
+            report(Ok("#.\n.#".into())).to_json(),
+            r##"{"day":10,"part":2,"status":"solved","answer":"#.\n.#","type":"multiline","duration_ms":1.500,"error":null}"##
+        );
+        assert_eq!(
+            report(Err(SolveError::TimedOut(Duration::from_secs(2)))).to_json(),
+            r##"{"day":10,"part":2,"status":"timeout","answer":null,"type":null,"duration_ms":1.500,"error":"day 10: timed out after 2.00s"}"##
+        );
+        assert_eq!(
+            report(Err(ParseError::new("unknown \"op\"").into())).to_json(),
+            r##"{"day":10,"part":2,"status":"error","answer":null,"type":null,"duration_ms":1.500,"error":"day 10: unknown \"op\""}"##
+        );
+    }
+}
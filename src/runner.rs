@@ -0,0 +1,554 @@
+use std::{
+    cell::Cell,
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Once},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::animation::{play, Playback};
+use crate::cancel::{self, CancelToken};
+use crate::cli::{Render, RunArgs};
+use crate::error::SolveError;
+use crate::params::Overrides;
+use crate::pool::run_in_order;
+use crate::report::{Format, Report};
+use crate::solution::{Answer, Parsed, Solution};
+use crate::timing::{format_duration, measure, Stats};
+use crate::trace::{debug, info, warn};
+use crate::utils::Input;
+
+/// A selected day with its input read and parsed, waiting for its parts to be solved.
+struct Loaded<'a> {
+    solution: &'a dyn Solution,
+    /// How long reading and parsing the input took, unless it could not be read.
+    times: Option<(Duration, Duration)>,
+    parsed: Result<Arc<dyn Parsed>, SolveError>,
+}
+
+/// Solves the selected parts on up to `args.jobs` threads, reporting them in order.
+pub fn solve<F>(registry: &[&dyn Solution], args: &RunArgs, mut on_report: F) -> bool
+where
+    F: FnMut(Report),
+{
+    let source = args.input.clone().unwrap_or_else(Input::from_env);
+    let mut succeeded = true;
+    let mut day_failed = false;
+    let mut days = vec![];
+
+    for solution in registry
+        .iter()
+        .filter(|solution| args.days.contains(&solution.day()))
+    {
+        let (input, load_time) = match load_input(*solution, &source, args) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                days.push(Loaded {
+                    solution: *solution,
+                    times: None,
+                    parsed: Err(err),
+                });
+                continue;
+            }
+        };
+
+        let (parsed, parse_time) =
+            measure(|| catch_panic(|| Ok(Arc::<dyn Parsed>::from(solution.parse(&input)?))));
+        info!(
+            "day {:02}: parsed in {}",
+            solution.day(),
+            format_duration(parse_time)
+        );
+
+        if let (Some(playback), Ok(parsed)) = (&args.animate, &parsed) {
+            succeeded &= play_animations(*solution, parsed.as_ref(), playback, args);
+        }
+
+        days.push(Loaded {
+            solution: *solution,
+            times: Some((load_time, parse_time)),
+            parsed,
+        });
+    }
+
+    let parts = args.parts();
+    let tasks = days
+        .iter()
+        .flat_map(|day| parts.iter().map(move |part| (day, *part)))
+        .collect::<Vec<_>>();
+
+    run_in_order(
+        tasks,
+        args.jobs,
+        |(day, part)| {
+            let (answer, elapsed) = match &day.parsed {
+                Ok(parsed) => {
+                    run_part_within(day.solution.day(), parsed, part, &args.params, args.timeout)
+                }
+                Err(err) => (Err(err.clone()), Duration::ZERO),
+            };
+
+            let report = Report {
+                day: day.solution.day(),
+                part,
+                answer,
+                elapsed,
+            };
+
+            (day, report)
+        },
+        |(day, report)| {
+            let part = report.part;
+            let show_times =
+                parts.first() == Some(&part) && args.time && args.format == Format::Text;
+
+            if let (Some((load_time, parse_time)), true) = (day.times, show_times) {
+                println!(
+                    "{:02} / input: loaded in {}",
+                    report.day,
+                    format_duration(load_time)
+                );
+                println!(
+                    "{:02} / parse: parsed in {}",
+                    report.day,
+                    format_duration(parse_time)
+                );
+            }
+
+            if parts.first() == Some(&part) {
+                day_failed = false;
+            }
+
+            day_failed |= report.answer.is_err();
+            succeeded &= report.answer.is_ok();
+            on_report(report);
+
+            // A day is drawn once all of its parts are reported, and only if they all succeeded
+            let last_part = parts.last() == Some(&part);
+
+            if let (Some(render), Ok(parsed), true) = (&args.render, &day.parsed, last_part) {
+                if day_failed {
+                    if args.format == Format::Text {
+                        println!(
+                            "{:02} / render: skipped as a part failed",
+                            day.solution.day()
+                        );
+                    }
+                } else {
+                    succeeded &= write_render(day.solution, parsed, render, args);
+                }
+            }
+        },
+    );
+
+    succeeded
+}
+
+pub fn bench(registry: &[&dyn Solution], args: &RunArgs, runs: usize) -> bool {
+    let source = args.input.clone().unwrap_or_else(Input::from_env);
+    let mut succeeded = true;
+
+    for solution in registry
+        .iter()
+        .filter(|solution| args.days.contains(&solution.day()))
+    {
+        let input = match load_input(*solution, &source, args) {
+            Ok((input, _)) => input,
+            Err(err) => {
+                eprintln!("error: {}", err.describe(solution.day()));
+                succeeded = false;
+                continue;
+            }
+        };
+
+        let mut samples = vec![];
+        let mut parsed = None;
+
+        for _ in 0..runs {
+            let (result, elapsed) = measure(|| catch_panic(|| Ok(solution.parse(&input)?)));
+
+            samples.push(elapsed);
+            parsed = Some(result);
+        }
+
+        let parsed = match parsed {
+            Some(Ok(parsed)) => parsed,
+            Some(Err(err)) => {
+                eprintln!("error: {}", err.describe(solution.day()));
+                succeeded = false;
+                continue;
+            }
+            None => continue,
+        };
+
+        if let Some(stats) = Stats::new(samples) {
+            println!("{:02} / parse: {stats}", solution.day());
+        }
+
+        'parts: for part in args.parts() {
+            let mut samples = vec![];
+
+            for _ in 0..runs {
+                let (answer, elapsed) =
+                    measure(|| catch_panic(|| run_part(parsed.as_ref(), part, &args.params)));
+
+                if let Err(err) = answer {
+                    eprintln!("error: {}", err.describe(solution.day()));
+                    succeeded = false;
+                    continue 'parts;
+                }
+
+                samples.push(elapsed);
+            }
+
+            if let Some(stats) = Stats::new(samples) {
+                println!("{:02} / {part:02}: {stats}", solution.day());
+            }
+        }
+    }
+
+    succeeded
+}
+
+pub fn run_part(
+    parsed: &dyn Parsed,
+    part: usize,
+    params: &Overrides,
+) -> Result<Answer, SolveError> {
+    match part {
+        1 => parsed.part_1(params),
+        _ => parsed.part_2(params),
+    }
+}
+
+/// How long a cancelled part is given to notice it, before it is left running.
+const CANCEL_GRACE: Duration = Duration::from_millis(500);
+
+/// Solves `part` within `timeout`, see [`run_within`].
+fn run_part_within(
+    day: usize,
+    parsed: &Arc<dyn Parsed>,
+    part: usize,
+    params: &Overrides,
+    timeout: Option<Duration>,
+) -> (Result<Answer, SolveError>, Duration) {
+    let (parsed, params) = (Arc::clone(parsed), params.clone());
+
+    run_within(day, &format!("part {part}"), timeout, move || {
+        run_part(parsed.as_ref(), part, &params)
+    })
+}
+
+/// Runs `task` on a thread of its own when there is a `timeout`, so the run can move on
+/// without it: a task still running by then is cancelled and reported as timed out.
+///
+/// Cancelling only stops tasks that call [`cancel::check`] as they go. Any other is left
+/// running, and keeps a core busy until the process exits.
+fn run_within<T, F>(
+    day: usize,
+    what: &str,
+    timeout: Option<Duration>,
+    task: F,
+) -> (Result<T, SolveError>, Duration)
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, SolveError> + Send + 'static,
+{
+    let Some(timeout) = timeout else {
+        return measure(|| catch_panic(task));
+    };
+
+    let token = CancelToken::new();
+    let (sender, receiver) = mpsc::channel();
+    let started = Instant::now();
+
+    let handle = {
+        let token = token.clone();
+
+        thread::spawn(move || {
+            let result = catch_panic(|| cancel::with_token(token, task));
+            sender.send(result).ok();
+        })
+    };
+
+    let received = receiver.recv_timeout(timeout);
+    let elapsed = started.elapsed();
+
+    let (result, stopped) = match received {
+        Ok(result) => (result, true),
+        Err(_) => {
+            token.cancel();
+
+            // A task checking the token answers soon after, the others are left running
+            let stopped = receiver.recv_timeout(CANCEL_GRACE).is_ok();
+
+            if !stopped {
+                warn!("day {day:02}: {what} does not check for cancellation, it is left running");
+            }
+
+            (Err(SolveError::TimedOut(timeout)), stopped)
+        }
+    };
+
+    if stopped {
+        handle.join().ok();
+    }
+
+    (result, elapsed)
+}
+
+thread_local! {
+    /// Set while [`catch_panic`] runs a solver on this thread, whose panics it reports itself.
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `solve`, turning a panic into an error so it fails only the day or part it happened in.
+fn catch_panic<T>(solve: impl FnOnce() -> Result<T, SolveError>) -> Result<T, SolveError> {
+    static HOOK: Once = Once::new();
+
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.get() {
+                default(info);
+            }
+        }));
+    });
+
+    let quiet = QUIET.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(solve));
+    QUIET.set(quiet);
+
+    result.unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "no message".to_owned());
+
+        Err(SolveError::Panicked(message))
+    })
+}
+
+/// Plays the day's animation of each selected part on stderr, reporting any error.
+fn play_animations(
+    solution: &dyn Solution,
+    parsed: &dyn Parsed,
+    playback: &Playback,
+    args: &RunArgs,
+) -> bool {
+    for part in args.parts() {
+        let played = catch_panic(|| {
+            let Some(mut animation) = parsed.animate(&args.params, part)? else {
+                return Ok(Ok(()));
+            };
+
+            Ok(play(animation.as_mut(), playback, &mut io::stderr().lock()))
+        });
+
+        let result = match played {
+            Ok(result) => result,
+            Err(err) => {
+                eprintln!("error: {}", err.describe(solution.day()));
+                return false;
+            }
+        };
+
+        if let Err(err) = result {
+            eprintln!(
+                "error: day {:02}: cannot play the animation: {err}",
+                solution.day()
+            );
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Draws the day's picture into the render directory, reporting any error.
+fn write_render(
+    solution: &dyn Solution,
+    parsed: &Arc<dyn Parsed>,
+    render: &Render,
+    args: &RunArgs,
+) -> bool {
+    let day = solution.day();
+    let (parsed, params) = (Arc::clone(parsed), args.params.clone());
+    let (image, _) = run_within(day, "the render", args.timeout, move || {
+        parsed.render(&params)
+    });
+
+    let image = match image {
+        Ok(Some(image)) => image,
+        Ok(None) => {
+            if args.format == Format::Text {
+                println!("{day:02} / render: nothing to draw");
+            }
+
+            return true;
+        }
+        Err(err) => {
+            eprintln!("error: {}", err.describe(day));
+            return false;
+        }
+    };
+
+    let path = render
+        .dir
+        .join(format!("{day:02}.{}", render.format.extension()));
+
+    match fs::create_dir_all(&render.dir)
+        .and_then(|_| fs::write(&path, image.encode(render.format)))
+    {
+        Ok(()) => {
+            if args.format == Format::Text {
+                println!("{day:02} / render: wrote {}", path.display());
+            }
+
+            true
+        }
+        Err(err) => {
+            eprintln!(
+                "error: day {day:02}: cannot write {}: {err}",
+                path.display()
+            );
+            false
+        }
+    }
+}
+
+/// Checks the parameter overrides apply to the day and reads its input.
+fn load_input(
+    solution: &dyn Solution,
+    source: &Input,
+    args: &RunArgs,
+) -> Result<(String, Duration), SolveError> {
+    args.params.check(solution.param_keys())?;
+
+    match measure(|| source.read(solution.day())) {
+        (Ok(input), elapsed) => {
+            debug!(
+                "day {:02}: read {} bytes of input",
+                solution.day(),
+                input.len()
+            );
+            Ok((input, elapsed))
+        }
+        (Err(err), _) => Err(SolveError::Input(err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+    use crate::cli::{parse, Command};
+    use crate::error::ParseError;
+    use crate::solution::Puzzle;
+    use crate::utils::image::Image;
+
+    fn run_args(args: &str) -> RunArgs {
+        match parse(args.split_whitespace().map(str::to_owned), &[1]) {
+            Ok(Command::Run(args)) => args,
+            command => panic!("expected a run, got {command:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_unreadable_inputs() {
+        let args = run_args("run 1 --input no/such/input.txt --format json");
+        let mut reports = vec![];
+
+        assert!(!solve(&crate::registry(), &args, |report| reports.push(report)));
+        assert_eq!(reports.len(), 2);
+
+        for report in reports {
+            assert!(matches!(report.answer, Err(SolveError::Input(_))));
+            assert!(report.to_json().contains(r#""status":"error""#));
+        }
+    }
+
+    /// Set once part 1 of [`Stuck`] stops looping.
+    static STOPPED: AtomicBool = AtomicBool::new(false);
+
+    struct Stuck;
+
+    impl Puzzle for Stuck {
+        const DAY: usize = 99;
+        const TITLE: &'static str = "Stuck";
+
+        type Parsed = ();
+        type Params = ();
+
+        fn parse(input: &str) -> Result<(), ParseError> {
+            match input {
+                "panic" => panic!("out of cheese"),
+                _ => Ok(()),
+            }
+        }
+
+        fn part_1(_: &(), _: &()) -> Result<Answer, SolveError> {
+            let result = (0..).try_for_each(|_: u64| cancel::check());
+            STOPPED.store(true, Ordering::Relaxed);
+
+            result.map(|_| Answer::Number(0))
+        }
+
+        fn part_2(_: &(), _: &()) -> Result<Answer, SolveError> {
+            panic!("out of cheese")
+        }
+
+        fn render(_: &(), _: &()) -> Result<Option<Image>, SolveError> {
+            (0..).try_for_each(|_: u64| cancel::check())?;
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn stops_renders_past_the_timeout() {
+        let args = run_args("run 1 --timeout 0.02 --render ppm");
+        let parsed = Arc::<dyn Parsed>::from(Stuck.parse("").unwrap());
+
+        let (written, elapsed) =
+            measure(|| write_render(&Stuck, &parsed, args.render.as_ref().unwrap(), &args));
+
+        assert!(!written);
+        assert!(elapsed < CANCEL_GRACE);
+    }
+
+    #[test]
+    fn stops_parts_past_their_timeout() {
+        let parsed = Arc::<dyn Parsed>::from(Stuck.parse("").unwrap());
+        let timeout = Duration::from_millis(20);
+
+        let (answer, elapsed) =
+            run_part_within(99, &parsed, 1, &Overrides::default(), Some(timeout));
+
+        assert_eq!(answer, Err(SolveError::TimedOut(timeout)));
+        assert!(elapsed < CANCEL_GRACE);
+        assert!(STOPPED.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn reports_panics() {
+        let parsed = Arc::<dyn Parsed>::from(Stuck.parse("").unwrap());
+
+        for timeout in [None, Some(Duration::from_secs(1))] {
+            let (answer, _) = run_part_within(99, &parsed, 2, &Overrides::default(), timeout);
+
+            assert_eq!(
+                answer,
+                Err(SolveError::Panicked("out of cheese".to_owned()))
+            );
+        }
+
+        assert!(matches!(
+            catch_panic(|| Ok(Stuck.parse("panic")?)),
+            Err(SolveError::Panicked(message)) if message == "out of cheese"
+        ));
+    }
+}
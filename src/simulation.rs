@@ -0,0 +1,142 @@
+use crate::cancel;
+use crate::error::SolveError;
+
+/// A puzzle solved one step at a time, whose state can be looked at between any two steps.
+pub trait Simulation {
+    type State;
+
+    fn step(&mut self);
+
+    fn is_done(&self) -> bool;
+
+    /// A snapshot of where the simulation is, copied out so it can be kept.
+    fn state(&self) -> Self::State;
+
+    fn run(&mut self) {
+        while !self.is_done() {
+            self.step();
+        }
+    }
+
+    /// Like [`Simulation::run`], but stops early once the part being solved is cancelled.
+    fn try_run(&mut self) -> Result<(), SolveError> {
+        while !self.is_done() {
+            cancel::check()?;
+            self.step();
+        }
+
+        Ok(())
+    }
+
+    /// The state before the first step, then after every step until done.
+    fn snapshots(self) -> Snapshots<Self>
+    where
+        Self: Sized,
+    {
+        Snapshots {
+            simulation: self,
+            started: false,
+        }
+    }
+
+    /// The same simulation with its states read by `f`, e.g. into animation frames. `f` borrows
+    /// the simulation, so nothing is cloned unless it keeps a copy.
+    fn map_state<T, F>(self, f: F) -> MapState<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self) -> T,
+    {
+        MapState {
+            simulation: self,
+            f,
+        }
+    }
+}
+
+pub struct Snapshots<S> {
+    simulation: S,
+    started: bool,
+}
+
+impl<S: Simulation> Iterator for Snapshots<S> {
+    type Item = S::State;
+
+    fn next(&mut self) -> Option<S::State> {
+        if !self.started {
+            self.started = true;
+        } else if self.simulation.is_done() {
+            return None;
+        } else {
+            self.simulation.step();
+        }
+
+        Some(self.simulation.state())
+    }
+}
+
+pub struct MapState<S, F> {
+    simulation: S,
+    f: F,
+}
+
+impl<S, F, T> Simulation for MapState<S, F>
+where
+    S: Simulation,
+    F: Fn(&S) -> T,
+{
+    type State = T;
+
+    fn step(&mut self) {
+        self.simulation.step();
+    }
+
+    fn is_done(&self) -> bool {
+        self.simulation.is_done()
+    }
+
+    fn state(&self) -> T {
+        (self.f)(&self.simulation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    struct Countdown(u32);
+
+    impl Simulation for Countdown {
+        type State = u32;
+
+        fn step(&mut self) {
+            self.0 -= 1;
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn state(&self) -> u32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn snapshots_every_step() {
+        assert_eq!(Countdown(3).snapshots().collect_vec(), [3, 2, 1, 0]);
+        assert_eq!(Countdown(0).snapshots().collect_vec(), [0]);
+        assert_eq!(
+            Countdown(2)
+                .map_state(|countdown| countdown.0 * 10)
+                .snapshots()
+                .collect_vec(),
+            [20, 10, 0]
+        );
+
+        let mut countdown = Countdown(5);
+        countdown.run();
+        assert_eq!(countdown.state(), 0);
+    }
+}
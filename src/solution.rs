@@ -0,0 +1,155 @@
+use std::fmt;
+
+use crate::animation::Animation;
+use crate::error::{ParseError, SolveError};
+use crate::params::{Overrides, Params};
+use crate::utils::image::Image;
+
+/// A day's puzzle: the input is parsed once and both parts solve from the parsed model.
+pub trait Puzzle {
+    const DAY: usize;
+    const TITLE: &'static str;
+
+    type Parsed: Send + Sync + 'static;
+    type Params: Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError>;
+
+    fn part_1(parsed: &Self::Parsed, params: &Self::Params) -> Result<Answer, SolveError>;
+
+    fn part_2(parsed: &Self::Parsed, params: &Self::Params) -> Result<Answer, SolveError>;
+
+    /// A picture of the puzzle once solved, for the days worth looking at.
+    fn render(_parsed: &Self::Parsed, _params: &Self::Params) -> Result<Option<Image>, SolveError> {
+        Ok(None)
+    }
+
+    /// The puzzle solving `part` step by step, for the days worth watching.
+    fn animate<'a>(
+        _parsed: &'a Self::Parsed,
+        _params: &Self::Params,
+        _part: usize,
+    ) -> Result<Option<Animation<'a>>, SolveError> {
+        Ok(None)
+    }
+}
+
+/// The object safe face of a [`Puzzle`], so days can be listed and run from one registry.
+pub trait Solution: Sync {
+    fn day(&self) -> usize;
+
+    fn title(&self) -> &'static str;
+
+    /// The parameters the day accepts as overrides, see [`crate::params::Params`].
+    fn param_keys(&self) -> &'static [&'static str];
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, ParseError>;
+}
+
+/// A parsed input, ready to solve either part as many times as needed, from any thread.
+pub trait Parsed: Send + Sync {
+    fn part_1(&self, params: &Overrides) -> Result<Answer, SolveError>;
+
+    fn part_2(&self, params: &Overrides) -> Result<Answer, SolveError>;
+
+    fn render(&self, params: &Overrides) -> Result<Option<Image>, SolveError>;
+
+    fn animate(&self, params: &Overrides, part: usize)
+        -> Result<Option<Animation<'_>>, SolveError>;
+}
+
+impl<P: Puzzle + Sync + 'static> Solution for P {
+    fn day(&self) -> usize {
+        P::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        P::TITLE
+    }
+
+    fn param_keys(&self) -> &'static [&'static str] {
+        P::Params::KEYS
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        Ok(Box::new(Model::<P>(P::parse(input)?)))
+    }
+}
+
+struct Model<P: Puzzle>(P::Parsed);
+
+impl<P: Puzzle> Parsed for Model<P> {
+    fn part_1(&self, params: &Overrides) -> Result<Answer, SolveError> {
+        P::part_1(&self.0, &P::Params::resolve(params)?)
+    }
+
+    fn part_2(&self, params: &Overrides) -> Result<Answer, SolveError> {
+        P::part_2(&self.0, &P::Params::resolve(params)?)
+    }
+
+    fn render(&self, params: &Overrides) -> Result<Option<Image>, SolveError> {
+        P::render(&self.0, &P::Params::resolve(params)?)
+    }
+
+    fn animate(
+        &self,
+        params: &Overrides,
+        part: usize,
+    ) -> Result<Option<Animation<'_>>, SolveError> {
+        P::animate(&self.0, &P::Params::resolve(params)?, part)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    Number(u64),
+    Text(String),
+}
+
+impl Answer {
+    pub fn is_multiline(&self) -> bool {
+        match self {
+            Self::Text(text) => text.contains('\n'),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Self::Number(value as u64)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+pub fn measure<T, F: FnOnce() -> T>(run: F) -> (T, Duration) {
+    let start = Instant::now();
+    let output = run();
+
+    (output, start.elapsed())
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+
+    if micros >= 1_000_000.0 {
+        format!("{:.2}s", micros / 1_000_000.0)
+    } else if micros >= 1_000.0 {
+        format!("{:.2}ms", micros / 1_000.0)
+    } else {
+        format!("{micros:.0}µs")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+    pub runs: usize,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<Duration>) -> Option<Self> {
+        samples.sort();
+
+        let runs = samples.len();
+        let middle = runs / 2;
+        let median = if runs.is_multiple_of(2) {
+            (*samples.get(middle.checked_sub(1)?)? + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+
+        Some(Self {
+            min: samples[0],
+            median,
+            max: samples[runs - 1],
+            runs,
+        })
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {}, median {}, max {} ({} runs)",
+            format_duration(self.min),
+            format_duration(self.median),
+            format_duration(self.max),
+            self.runs
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations_with_a_fitting_unit() {
+        assert_eq!(format_duration(Duration::from_nanos(1_499)), "1µs");
+        assert_eq!(format_duration(Duration::from_micros(2_345)), "2.35ms");
+        assert_eq!(format_duration(Duration::from_millis(12_340)), "12.34s");
+    }
+
+    #[test]
+    fn summarises_samples() {
+        let millis = |values: &[u64]| values.iter().map(|ms| Duration::from_millis(*ms)).collect();
+
+        assert_eq!(Stats::new(vec![]), None);
+        assert_eq!(
+            Stats::new(millis(&[5, 1, 3])),
+            Some(Stats {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(3),
+                max: Duration::from_millis(5),
+                runs: 3,
+            })
+        );
+        assert_eq!(
+            Stats::new(millis(&[4, 1, 2, 8])).map(|stats| stats.median),
+            Some(Duration::from_millis(3))
+        );
+    }
+}
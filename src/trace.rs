@@ -0,0 +1,248 @@
+use std::{env, fmt, str::FromStr, sync::OnceLock};
+
+/// How much a diagnostic matters, from the least to the most verbose.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// The level enabled by repeating `-v`, on top of the warnings shown by default.
+    pub fn from_verbosity(verbosity: usize) -> Self {
+        match verbosity {
+            0 => Self::Warn,
+            1 => Self::Info,
+            2 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = FilterError;
+
+    fn from_str(value: &str) -> Result<Self, FilterError> {
+        match value.to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(FilterError(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        };
+
+        f.pad(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterError(String);
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is not a valid level, expected error, warn, info, debug or trace",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// Which diagnostics are shown, written like `RUST_LOG`: `info,day_16=trace,runner=off`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    level: Option<Level>,
+    targets: Vec<(String, Option<Level>)>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            level: Some(Level::Warn),
+            targets: vec![],
+        }
+    }
+}
+
+impl Filter {
+    /// Shows at least the messages `verbosity` asks for, except from the targets turned off.
+    pub fn with_verbosity(mut self, verbosity: usize) -> Self {
+        if verbosity > 0 {
+            let level = Level::from_verbosity(verbosity);
+            self.level = self.level.max(Some(level));
+
+            for target_level in self
+                .targets
+                .iter_mut()
+                .filter_map(|(_, level)| level.as_mut())
+            {
+                *target_level = (*target_level).max(level);
+            }
+        }
+
+        self
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let target_level = self
+            .targets
+            .iter()
+            .filter(|(name, _)| {
+                target == name || target.starts_with(name) && target[name.len()..].starts_with("::")
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map_or(self.level, |(_, level)| *level);
+
+        target_level.is_some_and(|max| level <= max)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(spec: &str) -> Result<Self, FilterError> {
+        let mut filter = Self::default();
+        let level = |value: &str| match value {
+            "off" => Ok(None),
+            value => value.parse().map(Some),
+        };
+
+        for directive in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            match directive.split_once('=') {
+                Some((target, value)) => filter.targets.push((target.to_owned(), level(value)?)),
+                // A bare level sets the default, a bare target shows all of its messages
+                None => match level(directive) {
+                    Ok(value) => filter.level = value,
+                    Err(_) => filter
+                        .targets
+                        .push((directive.to_owned(), Some(Level::Trace))),
+                },
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+static FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// Sets the filter used by every diagnostic of the run: `filter`, else the one in `RUST_LOG`.
+pub fn init(filter: Option<Filter>, verbosity: usize) {
+    let filter = filter.unwrap_or_else(|| match env::var("RUST_LOG") {
+        Ok(spec) => spec.parse().unwrap_or_else(|err| {
+            eprintln!("warning: ignoring RUST_LOG: {err}");
+            Filter::default()
+        }),
+        Err(_) => Filter::default(),
+    });
+
+    FILTER.get_or_init(|| filter.with_verbosity(verbosity));
+}
+
+pub fn enabled(target: &str, level: Level) -> bool {
+    FILTER.get_or_init(Filter::default).enabled(target, level)
+}
+
+/// Writes a diagnostic to stderr, so it never mixes with the answers on stdout.
+pub fn write(target: &str, level: Level, message: fmt::Arguments) {
+    eprintln!("{level:>5} {target}: {message}");
+}
+
+/// The target of a module path, without the crate name, e.g. `day_16`.
+pub fn target(module_path: &'static str) -> &'static str {
+    module_path
+        .split_once("::")
+        .map_or(module_path, |(_, target)| target)
+}
+
+macro_rules! event {
+    ($level:expr, $($arg:tt)+) => {{
+        let target = $crate::trace::target(module_path!());
+
+        if $crate::trace::enabled(target, $level) {
+            $crate::trace::write(target, $level, format_args!($($arg)+));
+        }
+    }};
+}
+
+macro_rules! warn_event {
+    ($($arg:tt)+) => { $crate::trace::event!($crate::trace::Level::Warn, $($arg)+) };
+}
+
+macro_rules! info_event {
+    ($($arg:tt)+) => { $crate::trace::event!($crate::trace::Level::Info, $($arg)+) };
+}
+
+macro_rules! debug_event {
+    ($($arg:tt)+) => { $crate::trace::event!($crate::trace::Level::Debug, $($arg)+) };
+}
+
+macro_rules! trace_event {
+    ($($arg:tt)+) => { $crate::trace::event!($crate::trace::Level::Trace, $($arg)+) };
+}
+
+// Named apart from the built-in `warn` and `debug` attributes they would otherwise clash with
+pub(crate) use {
+    debug_event as debug, event, info_event as info, trace_event as trace, warn_event as warn,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_level_and_target() {
+        let filter: Filter = "info,day_16=trace,day_07=off,runner".parse().unwrap();
+
+        assert!(filter.enabled("day_01", Level::Info));
+        assert!(!filter.enabled("day_01", Level::Debug));
+        assert!(filter.enabled("day_16", Level::Trace));
+        assert!(filter.enabled("day_16::volcano", Level::Trace));
+        assert!(!filter.enabled("day_161", Level::Debug));
+        assert!(!filter.enabled("day_07", Level::Error));
+        assert!(filter.enabled("runner", Level::Trace));
+
+        assert_eq!(
+            "day_16=loud".parse::<Filter>().unwrap_err().to_string(),
+            "'loud' is not a valid level, expected error, warn, info, debug or trace"
+        );
+    }
+
+    #[test]
+    fn verbosity_raises_every_level_but_off() {
+        let filter = Filter::default();
+        assert!(filter.enabled("day_01", Level::Warn));
+        assert!(!filter.enabled("day_01", Level::Info));
+
+        let filter = "error,day_07=info,day_16=off"
+            .parse::<Filter>()
+            .unwrap()
+            .with_verbosity(2);
+        assert!(filter.enabled("day_01", Level::Debug));
+        assert!(filter.enabled("day_07", Level::Debug));
+        assert!(!filter.enabled("day_07", Level::Trace));
+        assert!(!filter.enabled("day_16", Level::Error));
+    }
+}
@@ -1,31 +1,203 @@
 use std::{
-    fs::{read_to_string, File},
-    io::{BufRead, BufReader},
+    env, fmt,
+    fs::read_to_string,
+    io::{self, Read},
+    path::PathBuf,
+    str::FromStr,
 };
 
 use itertools::Itertools;
 
-pub fn read_lines(day_number: usize) -> Vec<String> {
-    let file = File::open(format!("inputs/{:02}.txt", day_number)).unwrap();
-    let reader = BufReader::new(file);
+use crate::error::ParseError;
 
-    reader.lines().map(|l| l.unwrap()).collect()
+pub mod grid;
+pub mod image;
+pub mod interval;
+pub mod ocr;
+pub mod parse;
+pub mod point;
+pub mod search;
+
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+const DEFAULT_INPUT_DIR: &str = "inputs";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    File(PathBuf),
+    Stdin,
+    Dir(PathBuf),
+}
+
+impl Input {
+    pub fn from_env() -> Self {
+        let dir = env::var_os(INPUT_DIR_VAR).unwrap_or_else(|| DEFAULT_INPUT_DIR.into());
+
+        Self::Dir(PathBuf::from(dir))
+    }
+
+    pub fn read(&self, day_number: usize) -> Result<String, InputError> {
+        match self {
+            Self::File(path) => read_file(path.clone()),
+            Self::Dir(dir) => read_file(dir.join(format!("{:02}.txt", day_number))),
+            Self::Stdin => {
+                let mut input = String::new();
+
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map(|_| input)
+                    .map_err(|err| InputError { path: None, err })
+            }
+        }
+    }
+}
+
+fn read_file(path: PathBuf) -> Result<String, InputError> {
+    read_to_string(&path).map_err(|err| InputError {
+        path: Some(path),
+        err,
+    })
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    path: Option<PathBuf>,
+    err: io::Error,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "could not read '{}': {}", path.display(), self.err)?,
+            None => write!(f, "could not read stdin: {}", self.err)?,
+        }
+
+        if self.err.kind() == io::ErrorKind::NotFound {
+            write!(
+                f,
+                " (pass --input FILE, or point --input-dir / {INPUT_DIR_VAR} at your inputs)"
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
-pub fn read_input_to_string(day_number: usize) -> String {
-    read_to_string(format!("inputs/{:02}.txt", day_number))
-        .expect(&format!("Tried to read {day_number}"))
+/// A run of consecutive non-blank lines, see [`records`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record<'a> {
+    /// Counted from 1, like the lines.
+    pub number: usize,
+    pub first_line: usize,
+    /// Without trailing whitespace.
+    pub lines: Vec<&'a str>,
 }
 
-pub fn string_to_lines(str: &str) -> Vec<String> {
-    str.lines().map(|line| line.to_owned()).collect_vec()
+impl<'a> Record<'a> {
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Parses every line of the record, attaching the line number to any error.
+    pub fn parse_lines<T, F>(&self, mut parse: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&'a str) -> Result<T, ParseError>,
+    {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                parse(line).map_err(|err| err.in_line(self.first_line + index, line))
+            })
+            .collect()
+    }
+}
+
+/// Splits the input into records separated by one or more blank lines, with or without CRLF.
+pub fn records(input: &str) -> Vec<Record<'_>> {
+    let mut records = vec![];
+    let lines = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim_end()))
+        .chunk_by(|(_, line)| line.is_empty());
+
+    for (_, lines) in lines.into_iter().filter(|(blank, _)| !blank) {
+        let lines = lines.collect_vec();
+
+        records.push(Record {
+            number: records.len() + 1,
+            first_line: lines[0].0,
+            lines: lines.into_iter().map(|(_, line)| line).collect(),
+        });
+    }
+
+    records
+}
+
+/// Parses every record, attaching the record number to any error.
+pub fn parse_records<T, F>(input: &str, mut parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&Record) -> Result<T, ParseError>,
+{
+    records(input)
+        .iter()
+        .map(|record| parse(record).map_err(|err| err.in_record(record.number)))
+        .collect()
 }
 
-pub fn read_input_split_by_lines_number(day_number: usize, lines: usize) -> Vec<String> {
-    read_input_to_string(day_number)
-        .split("\n")
-        .collect_vec()
-        .chunks(lines)
-        .map(|chunk| chunk.join("\n"))
-        .collect_vec()
+/// Parses every non-blank line, attaching the line number to any error.
+pub fn parse_lines<T, F>(input: &str, parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim_end()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| parse(line).map_err(|err| err.in_line(index + 1, line)))
+        .collect()
+}
+
+/// Parses `part`, a slice of `line`, as a number.
+pub fn parse_number<T: FromStr>(line: &str, part: &str) -> Result<T, ParseError> {
+    part.parse::<T>()
+        .map_err(|_| ParseError::new(format!("expected a number, got '{part}'")).at(line, part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_records_on_blank_lines() {
+        let records = records("1\r\n2  \r\n\r\n \n\n3\n4\n5");
+
+        assert_eq!(
+            records,
+            [
+                Record {
+                    number: 1,
+                    first_line: 1,
+                    lines: vec!["1", "2"],
+                },
+                Record {
+                    number: 2,
+                    first_line: 6,
+                    lines: vec!["3", "4", "5"],
+                },
+            ]
+        );
+
+        let err = parse_records("1\n\n2\nx", |record| {
+            record.parse_lines(|line| parse_number::<u32>(line, line))
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "record 2, line 4, column 1: expected a number, got 'x'\n   |\n 4 | x\n   | ^"
+        );
+    }
 }
@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+use crate::error::ParseError;
+
+use super::point::{Direction, Direction8, Point};
+
+/// A position in a grid, with `y` growing downwards.
+pub type Pos = Point<usize>;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, ParseError> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+
+        if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(ParseError::new(format!(
+                "row {} has {} cells, expected {width}",
+                index + 1,
+                row.len()
+            )));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses a character map, one row per non-blank line.
+    pub fn parse<F>(input: &str, mut parse: F) -> Result<Self, ParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+
+        for (index, line) in input.lines().map(str::trim_end).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            for (offset, char) in line.char_indices() {
+                let cell = parse(char).ok_or_else(|| {
+                    ParseError::new(format!("unexpected '{char}' in the map"))
+                        .at(line, &line[offset..])
+                        .in_line(index + 1, line)
+                })?;
+
+                cells.push(cell);
+            }
+
+            let columns = line.chars().count();
+
+            if *width.get_or_insert(columns) != columns {
+                return Err(ParseError::new(format!(
+                    "expected {} columns, got {columns}",
+                    width.unwrap_or(0)
+                ))
+                .in_line(index + 1, line));
+            }
+
+            height += 1;
+        }
+
+        if height == 0 {
+            return Err(ParseError::new("expected a map, got no rows"));
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.y * self.width + pos.x])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.y * self.width + pos.x])
+    }
+
+    /// The position one `delta` away from `pos`, if it is still inside the grid.
+    pub fn step(&self, pos: Pos, delta: impl Into<Point<isize>>) -> Option<Pos> {
+        pos.step(delta).filter(|pos| self.contains(*pos))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+
+        (0..self.cells.len()).map(move |index| Point::new(index % width, index / width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn position<F>(&self, mut predicate: F) -> Option<Pos>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The up to 4 orthogonal neighbours of `pos`.
+    pub fn neighbours(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(pos, direction))
+    }
+
+    /// The up to 8 neighbours of `pos`, diagonals included.
+    pub fn all_neighbours(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction8::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(pos, direction))
+    }
+
+    /// Every position from `pos` (excluded) towards `delta` until the edge of the grid.
+    pub fn ray(&self, pos: Pos, delta: impl Into<Point<isize>>) -> impl Iterator<Item = Pos> + '_ {
+        let delta = delta.into();
+        let mut current = Some(pos);
+
+        std::iter::from_fn(move || {
+            current = self.step(current?, delta);
+            current
+        })
+    }
+
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> {
+        self.cells[y * self.width..(y + 1) * self.width].iter()
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        self.cells[x..].iter().step_by(self.width.max(1))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn map<U, F>(&self, mut f: F) -> Grid<U>
+    where
+        F: FnMut(Pos, &T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.iter().map(|(pos, cell)| f(pos, cell)).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .flat_map(|x| self.column(x).cloned())
+                .collect(),
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside of the grid"))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside of the grid"))
+    }
+}
+
+/// A position in an unbounded grid.
+pub type SparsePos = Point<isize>;
+
+/// An unbounded grid storing only the cells that were set.
+#[derive(Clone, Debug, Default)]
+pub struct SparseGrid<T> {
+    cells: HashMap<SparsePos, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, pos: SparsePos) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    pub fn get(&self, pos: SparsePos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn insert(&mut self, pos: SparsePos, value: T) -> Option<T> {
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: SparsePos) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SparsePos, &T)> {
+        self.cells.iter().map(|(pos, value)| (*pos, value))
+    }
+
+    /// The smallest and largest corner enclosing every set cell.
+    pub fn bounds(&self) -> Option<(SparsePos, SparsePos)> {
+        self.cells.keys().fold(None, |bounds, pos| match bounds {
+            None => Some((*pos, *pos)),
+            Some((min, max)) => Some((min.min(*pos), max.max(*pos))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(input, |char| char.to_digit(10))
+    }
+
+    #[test]
+    fn parses_and_walks_a_character_map() {
+        let grid = digits("123\n456\n").unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], 6);
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(
+            grid.neighbours(Point::new(0, 0)).collect_vec(),
+            [Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(grid.all_neighbours(Point::new(1, 0)).count(), 5);
+        assert_eq!(
+            grid.ray(Point::new(0, 1), Direction::Right).collect_vec(),
+            [Point::new(1, 1), Point::new(2, 1)]
+        );
+        assert_eq!(grid.row(1).rev().collect_vec(), [&6, &5, &4]);
+        assert_eq!(grid.column(1).collect_vec(), [&2, &5]);
+        assert_eq!(grid.transpose(), digits("14\n25\n36").unwrap());
+    }
+
+    #[test]
+    fn reports_malformed_maps() {
+        assert_eq!(
+            digits("12\n3x").unwrap_err().to_string(),
+            "line 2, column 2: unexpected 'x' in the map\n   |\n 2 | 3x\n   |  ^"
+        );
+        assert_eq!(
+            digits("12\n345").unwrap_err().to_string(),
+            "line 2: expected 2 columns, got 3\n   |\n 2 | 345"
+        );
+    }
+
+    #[test]
+    fn tracks_sparse_bounds() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(2, -1), '#');
+        grid.insert(Point::new(-3, 4), '#');
+
+        assert_eq!(grid.bounds(), Some((Point::new(-3, -1), Point::new(2, 4))));
+        assert!(grid.contains(Point::new(2, -1)));
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt::Write;
+
+use super::grid::{Grid, Pos, SparseGrid, SparsePos};
+use super::point::Point;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Self = Self(0, 0, 0);
+    pub const WHITE: Self = Self(255, 255, 255);
+
+    /// The colour `t` of the way from `self` to `other`, with `t` between 0 and 1.
+    pub fn mix(self, other: Self, t: f64) -> Self {
+        let channel =
+            |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+
+        Self(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Png => "png",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+/// A picture made of square cells, each drawn `scale` pixels wide.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+    scale: usize,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self {
+            pixels: Grid::new(width, height, background),
+            scale: 1,
+        }
+    }
+
+    pub fn from_grid<T, F>(grid: &Grid<T>, mut color: F) -> Self
+    where
+        F: FnMut(Pos, &T) -> Rgb,
+    {
+        Self {
+            pixels: grid.map(|pos, cell| color(pos, cell)),
+            scale: 1,
+        }
+    }
+
+    /// The cells within the bounds of `grid`, with `background` where none is set.
+    pub fn from_sparse<T, F>(grid: &SparseGrid<T>, background: Rgb, mut color: F) -> Self
+    where
+        F: FnMut(SparsePos, &T) -> Rgb,
+    {
+        let Some((min, max)) = grid.bounds() else {
+            return Self::new(0, 0, background);
+        };
+
+        let size = max - min + Point::new(1, 1);
+        let mut image = Self::new(size.x as usize, size.y as usize, background);
+
+        for (pos, cell) in grid.iter() {
+            let offset = pos - min;
+            image.set(
+                Point::new(offset.x as usize, offset.y as usize),
+                color(pos, cell),
+            );
+        }
+
+        image
+    }
+
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.width() * self.scale
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.height() * self.scale
+    }
+
+    /// The colour of the cell at `pos`, whatever the scale.
+    pub fn get(&self, pos: Pos) -> Option<Rgb> {
+        self.pixels.get(pos).copied()
+    }
+
+    pub fn set(&mut self, pos: Pos, color: Rgb) {
+        if let Some(pixel) = self.pixels.get_mut(pos) {
+            *pixel = color;
+        }
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            ImageFormat::Ppm => self.ppm(),
+            ImageFormat::Png => self.png(),
+            ImageFormat::Svg => self.svg().into_bytes(),
+        }
+    }
+
+    /// The scaled pixels, row by row.
+    fn scanlines(&self) -> impl Iterator<Item = Vec<Rgb>> + '_ {
+        self.pixels.rows().flat_map(|row| {
+            let line = row
+                .iter()
+                .flat_map(|pixel| std::iter::repeat_n(*pixel, self.scale))
+                .collect::<Vec<_>>();
+
+            std::iter::repeat_n(line, self.scale)
+        })
+    }
+
+    fn ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width(), self.height()).into_bytes();
+
+        for line in self.scanlines() {
+            bytes.extend(line.iter().flat_map(|Rgb(r, g, b)| [*r, *g, *b]));
+        }
+
+        bytes
+    }
+
+    fn png(&self) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend((self.width() as u32).to_be_bytes());
+        header.extend((self.height() as u32).to_be_bytes());
+        // 8 bits per channel, RGB, default compression, filter and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        // Every scanline starts with its filter type, 0 for none
+        let mut raw = vec![];
+
+        for line in self.scanlines() {
+            raw.push(0);
+            raw.extend(line.iter().flat_map(|Rgb(r, g, b)| [*r, *g, *b]));
+        }
+
+        let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut bytes, b"IHDR", &header);
+        png_chunk(&mut bytes, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut bytes, b"IEND", &[]);
+
+        bytes
+    }
+
+    /// One rectangle per run of same coloured cells in a row.
+    fn svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n",
+            self.width(),
+            self.height()
+        );
+
+        for (y, row) in self.pixels.rows().enumerate() {
+            let mut x = 0;
+
+            for run in row.chunk_by(|a, b| a == b) {
+                let Rgb(r, g, b) = run[0];
+
+                writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{r:02x}{g:02x}{b:02x}\"/>",
+                    x * self.scale,
+                    y * self.scale,
+                    run.len() * self.scale,
+                    self.scale
+                )
+                .unwrap();
+
+                x += run.len();
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn png_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend((data.len() as u32).to_be_bytes());
+    bytes.extend(kind);
+    bytes.extend(data);
+    bytes.extend(crc32(kind.iter().chain(data)).to_be_bytes());
+}
+
+/// A zlib stream of uncompressed deflate blocks, which every PNG reader understands.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = u16::MAX as usize;
+
+    let mut bytes = vec![0x78, 0x01];
+    let blocks = data.chunks(BLOCK).collect::<Vec<_>>();
+
+    for (index, block) in blocks.iter().enumerate() {
+        let last = index + 1 == blocks.len();
+        let length = block.len() as u16;
+
+        bytes.push(u8::from(last));
+        bytes.extend(length.to_le_bytes());
+        bytes.extend((!length).to_le_bytes());
+        bytes.extend(*block);
+    }
+
+    if blocks.is_empty() {
+        bytes.extend([1, 0, 0, 0xff, 0xff]);
+    }
+
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let crc = bytes.into_iter().fold(u32::MAX, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    });
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1, 0), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Image {
+        let grid = Grid::from_rows(vec![vec![true, false], vec![false, true]]).unwrap();
+
+        Image::from_grid(&grid, |_, on| if *on { Rgb::WHITE } else { Rgb::BLACK })
+    }
+
+    #[test]
+    fn encodes_every_format() {
+        let image = checkerboard().with_scale(2);
+
+        let ppm = image.encode(ImageFormat::Ppm);
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+        assert_eq!(ppm[11..17], [255, 255, 255, 255, 255, 255]);
+
+        let png = image.encode(ImageFormat::Png);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x04\0\0\0\x04"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+
+        let svg = String::from_utf8(image.encode(ImageFormat::Svg)).unwrap();
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert!(svg.contains("<rect x=\"2\" y=\"0\" width=\"2\" height=\"2\" fill=\"#000000\"/>"));
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn places_sparse_cells_within_their_bounds() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-1, 5), ());
+        grid.insert(Point::new(1, 6), ());
+
+        let image = Image::from_sparse(&grid, Rgb::BLACK, |_, _| Rgb::WHITE);
+
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.get(Point::new(0, 0)), Some(Rgb::WHITE));
+        assert_eq!(image.get(Point::new(1, 0)), Some(Rgb::BLACK));
+    }
+}
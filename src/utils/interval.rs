@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+use super::point::Number;
+
+/// A set of numbers stored as sorted, disjoint, half-open ranges.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntervalSet<N> {
+    ranges: Vec<Range<N>>,
+}
+
+impl<N: Number> IntervalSet<N> {
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    /// Adds `range`, merging it with every range it overlaps or touches.
+    pub fn insert(&mut self, range: Range<N>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let from = self.ranges.partition_point(|other| other.end < range.start);
+        let to = self
+            .ranges
+            .partition_point(|other| other.start <= range.end);
+
+        let merged = match self.ranges.get(from..to) {
+            Some([first, .., last]) | Some([first @ last]) => {
+                range.start.min(first.start)..range.end.max(last.end)
+            }
+            _ => range,
+        };
+
+        self.ranges.splice(from..to, [merged]);
+    }
+
+    pub fn ranges(&self) -> &[Range<N>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn total_length(&self) -> N {
+        self.ranges
+            .iter()
+            .fold(N::ZERO, |total, range| total + (range.end - range.start))
+    }
+
+    pub fn contains(&self, value: N) -> bool {
+        self.contains_range(&(value..value + N::ONE))
+    }
+
+    pub fn contains_range(&self, range: &Range<N>) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+
+        let index = self
+            .ranges
+            .partition_point(|other| other.end <= range.start);
+
+        self.ranges
+            .get(index)
+            .is_some_and(|other| other.start <= range.start && range.end <= other.end)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        union.extend(other.ranges.iter().cloned());
+
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut left, mut right) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+
+            if a.end < b.end {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        match (self.ranges.first(), self.ranges.last()) {
+            (Some(first), Some(last)) => {
+                self.intersection(&other.complement(first.start..last.end))
+            }
+            _ => Self::new(),
+        }
+    }
+
+    /// Every value within `bounds` that is not in the set.
+    pub fn complement(&self, bounds: Range<N>) -> Self {
+        let mut ranges = vec![];
+        let mut cursor = bounds.start;
+
+        for range in &self.ranges {
+            if range.start >= bounds.end {
+                break;
+            }
+
+            if range.start > cursor {
+                ranges.push(cursor..range.start);
+            }
+
+            cursor = cursor.max(range.end);
+        }
+
+        if cursor < bounds.end {
+            ranges.push(cursor..bounds.end);
+        }
+
+        Self { ranges }
+    }
+
+    /// The holes between consecutive ranges of the set.
+    pub fn gaps(&self) -> impl Iterator<Item = Range<N>> + '_ {
+        self.ranges
+            .windows(2)
+            .map(|pair| pair[0].end..pair[1].start)
+    }
+}
+
+impl<N: Number> From<Range<N>> for IntervalSet<N> {
+    fn from(range: Range<N>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+
+        set
+    }
+}
+
+impl<N: Number> Extend<Range<N>> for IntervalSet<N> {
+    fn extend<I: IntoIterator<Item = Range<N>>>(&mut self, ranges: I) {
+        for range in ranges {
+            self.insert(range);
+        }
+    }
+}
+
+impl<N: Number> FromIterator<Range<N>> for IntervalSet<N> {
+    fn from_iter<I: IntoIterator<Item = Range<N>>>(ranges: I) -> Self {
+        let mut set = Self::new();
+        set.extend(ranges);
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn merges_overlapping_and_touching_ranges() {
+        let set: IntervalSet<i32> = [5..8, -2..1, 1..3, 7..10, 12..12, 20..25]
+            .into_iter()
+            .collect();
+
+        assert_eq!(set.ranges(), [-2..3, 5..10, 20..25]);
+        assert_eq!(set.total_length(), 15);
+        assert_eq!(set.gaps().collect_vec(), [3..5, 10..20]);
+        assert!(set.contains(9) && !set.contains(10));
+        assert!(set.contains_range(&(6..9)) && !set.contains_range(&(2..6)));
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a: IntervalSet<u32> = [0..10, 20..30].into_iter().collect();
+        let b = IntervalSet::from(5..25);
+
+        assert_eq!(a.union(&b), IntervalSet::from(0..30));
+        assert_eq!(a.intersection(&b).ranges(), [5..10, 20..25]);
+        assert_eq!(a.difference(&b).ranges(), [0..5, 25..30]);
+        assert_eq!(a.complement(2..40).ranges(), [10..20, 30..40]);
+    }
+}
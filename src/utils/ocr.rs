@@ -0,0 +1,200 @@
+use itertools::Itertools;
+
+use crate::error::SolveError;
+
+use super::grid::Grid;
+use super::point::Point;
+
+/// The 6 pixels tall font, with the blank columns around each letter trimmed.
+const SMALL: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// The 10 pixels tall font, with the blank columns around each letter trimmed.
+const LARGE: [(char, &str); 15] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+/// Reads the capital letters drawn by the lit pixels, in either of the fonts the puzzles use.
+pub fn read(pixels: &Grid<bool>) -> Result<String, SolveError> {
+    let lit_rows = (0..pixels.height())
+        .filter(|y| pixels.row(*y).any(|lit| *lit))
+        .collect_vec();
+
+    let (Some(&top), Some(&bottom)) = (lit_rows.first(), lit_rows.last()) else {
+        return Err(SolveError::no_answer("no letters are drawn"));
+    };
+
+    let font = match bottom - top + 1 {
+        6 => &SMALL[..],
+        10 => &LARGE[..],
+        height => {
+            return Err(SolveError::no_answer(format!(
+                "letters are 6 or 10 pixels tall, these are {height}:\n{}",
+                draw(pixels)
+            )))
+        }
+    };
+
+    let columns = (0..pixels.width()).chunk_by(|x| pixels.column(*x).any(|lit| *lit));
+    let mut letters = String::new();
+
+    for (_, columns) in columns.into_iter().filter(|(lit, _)| *lit) {
+        let columns = columns.collect_vec();
+        let glyph = (top..=bottom)
+            .map(|y| {
+                columns
+                    .iter()
+                    .map(|x| if pixels[Point::new(*x, y)] { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .join("\n");
+
+        let (letter, _) = font
+            .iter()
+            .find(|(_, pattern)| *pattern == glyph)
+            .ok_or_else(|| {
+                SolveError::no_answer(format!(
+                    "unknown letter at column {}:\n{glyph}",
+                    columns[0] + 1
+                ))
+            })?;
+
+        letters.push(*letter);
+    }
+
+    Ok(letters)
+}
+
+/// The pixels as text, `#` for the lit ones.
+pub fn draw(pixels: &Grid<bool>) -> String {
+    pixels
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|lit| if *lit { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(art: &str) -> Grid<bool> {
+        Grid::parse(art, |char| match char {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_both_fonts() {
+        let small = pixels(
+            "\
+......................
+.##..###..#..#.###....
+#..#.#..#.#..#..#.....
+#..#.###..####..#.....
+####.#..#.#..#..#.....
+#..#.#..#.#..#..#.....
+#..#.###..#..#.###....",
+        );
+        let large = pixels(
+            "\
+#....#..#....#
+##...#..#....#
+##...#...#..#.
+#.#..#...#..#.
+#.#..#....##..
+#..#.#....##..
+#..#.#...#..#.
+#...##...#..#.
+#...##..#....#
+#....#..#....#",
+        );
+
+        assert_eq!(read(&small), Ok("ABHI".to_owned()));
+        assert_eq!(read(&large), Ok("NX".to_owned()));
+        assert!(read(&pixels("#.#\n.#.\n#.#")).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use crate::error::ParseError;
+
+use super::parse_number;
+
+/// A `&'static Regex` compiled on first use, instead of on every call.
+macro_rules! pattern {
+    ($pattern:literal) => {{
+        static PATTERN: std::sync::LazyLock<regex::Regex> =
+            std::sync::LazyLock::new(|| regex::Regex::new($pattern).unwrap());
+
+        &*PATTERN
+    }};
+}
+
+pub(crate) use pattern;
+
+/// Runs `parser` over `line`, which has to consume all of it.
+pub fn complete<'a, T, F>(line: &'a str, parser: F) -> Result<T, ParseError>
+where
+    F: FnOnce(&mut Cursor<'a>) -> Result<T, ParseError>,
+{
+    let mut cursor = Cursor::new(line);
+    let value = parser(&mut cursor)?;
+    cursor.end()?;
+
+    Ok(value)
+}
+
+/// A position within a line, moved forward by each parser that succeeds.
+#[derive(Clone, Copy, Debug)]
+pub struct Cursor<'a> {
+    line: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(line: &'a str) -> Self {
+        Self { line, rest: line }
+    }
+
+    /// The part of the line not consumed yet.
+    pub fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    /// An error pointing at the current position.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(message).at(self.line, self.rest)
+    }
+
+    /// An error pointing at `part`, something this cursor returned earlier.
+    pub fn error_at(&self, part: &str, message: impl Into<String>) -> ParseError {
+        ParseError::new(message).at(self.line, part)
+    }
+
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        match self.rest.strip_prefix(tag) {
+            Some(rest) => {
+                self.rest = rest;
+                Ok(())
+            }
+            None => Err(self.error(format!("expected '{tag}', got '{}'", self.token()))),
+        }
+    }
+
+    /// Consumes the first of `tags` the line continues with.
+    pub fn one_of(&mut self, tags: &[&'static str]) -> Result<&'static str, ParseError> {
+        let tag = tags
+            .iter()
+            .find(|tag| self.rest.starts_with(**tag))
+            .ok_or_else(|| {
+                let expected = tags
+                    .iter()
+                    .map(|tag| format!("'{tag}'"))
+                    .collect::<Vec<_>>();
+
+                self.error(format!(
+                    "expected one of {}, got '{}'",
+                    expected.join(", "),
+                    self.token()
+                ))
+            })?;
+
+        self.rest = &self.rest[tag.len()..];
+        Ok(tag)
+    }
+
+    pub fn take_while<F: FnMut(char) -> bool>(&mut self, mut predicate: F) -> &'a str {
+        let length = self
+            .rest
+            .find(|char| !predicate(char))
+            .unwrap_or(self.rest.len());
+        let (taken, rest) = self.rest.split_at(length);
+        self.rest = rest;
+
+        taken
+    }
+
+    /// A non-empty run of letters and digits.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        match self.take_while(char::is_alphanumeric) {
+            "" => Err(self.error(format!("expected a word, got '{}'", self.token()))),
+            word => Ok(word),
+        }
+    }
+
+    pub fn unsigned<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        self.number(0)
+    }
+
+    /// A number with an optional leading sign.
+    pub fn signed<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        self.number(usize::from(self.rest.starts_with(['-', '+'])))
+    }
+
+    fn number<T: FromStr>(&mut self, sign: usize) -> Result<T, ParseError> {
+        let digits = self.rest[sign..]
+            .find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(self.rest.len() - sign);
+
+        if digits == 0 {
+            return Err(self.error(format!("expected a number, got '{}'", self.token())));
+        }
+
+        let (number, rest) = self.rest.split_at(sign + digits);
+        let number = parse_number(self.line, number)?;
+        self.rest = rest;
+
+        Ok(number)
+    }
+
+    /// A value introduced by `label`, e.g. `x=` in `x=-3`.
+    pub fn field<T, F>(&mut self, label: &str, value: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ParseError>,
+    {
+        self.tag(label)?;
+        value(self)
+    }
+
+    /// One or more items parsed by `item` with `separator` between them.
+    pub fn separated<T, F>(&mut self, separator: &str, mut item: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&mut Self) -> Result<T, ParseError>,
+    {
+        let mut items = vec![item(self)?];
+
+        while let Some(rest) = self.rest.strip_prefix(separator) {
+            self.rest = rest;
+            items.push(item(self)?);
+        }
+
+        Ok(items)
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        match self.rest {
+            "" => Ok(()),
+            rest => Err(self.error(format!("unexpected '{rest}' at the end"))),
+        }
+    }
+
+    /// The next token, to show in errors.
+    fn token(&self) -> &'a str {
+        let length = self
+            .rest
+            .find(|char: char| !char.is_alphanumeric() && char != '-' && char != '+')
+            .unwrap_or(self.rest.len());
+
+        match length {
+            0 => self.rest.get(..1).unwrap_or_default(),
+            _ => &self.rest[..length],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_labelled_lists() {
+        let parsed = complete("at x=-3, y=+4: 1, 22, 333", |cursor| {
+            cursor.tag("at ")?;
+            let x = cursor.field("x=", Cursor::signed::<i32>)?;
+            let y = cursor.field(", y=", Cursor::signed::<i32>)?;
+            cursor.tag(": ")?;
+
+            Ok((x, y, cursor.separated(", ", Cursor::unsigned::<u32>)?))
+        });
+
+        assert_eq!(parsed, Ok((-3, 4, vec![1, 22, 333])));
+        assert!(pattern!(r"^\d+$").is_match("123"));
+    }
+
+    #[test]
+    fn points_errors_at_the_failure() {
+        let error = |line, parser: fn(&mut Cursor) -> Result<u8, ParseError>| {
+            complete(line, parser).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            error("x=five", |cursor| cursor.field("x=", Cursor::unsigned)),
+            "column 3: expected a number, got 'five'"
+        );
+        assert_eq!(
+            error("x=300", |cursor| cursor.field("x=", Cursor::unsigned)),
+            "column 3: expected a number, got '300'"
+        );
+        assert_eq!(
+            error("y=3", |cursor| cursor.field("x=", Cursor::unsigned)),
+            "column 1: expected 'x=', got 'y'"
+        );
+        assert_eq!(
+            error("1 2", |cursor| cursor.unsigned()),
+            "column 2: unexpected ' 2' at the end"
+        );
+    }
+}
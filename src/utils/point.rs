@@ -0,0 +1,357 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// The integer types [`Point`] and the other utils are generic over.
+pub trait Number:
+    Copy + Ord + fmt::Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// The distance between both values, which for a signed type has to fit in it: this holds
+    /// whenever both lie within half its range of zero, and panics otherwise.
+    fn abs_diff(self, other: Self) -> Self {
+        self.checked_abs_diff(other)
+            .expect("the distance fits in the type")
+    }
+
+    /// The distance between both values, or `None` if it does not fit in the type.
+    fn checked_abs_diff(self, other: Self) -> Option<Self>;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    fn checked_add_signed(self, delta: isize) -> Option<Self>;
+}
+
+pub trait Signed: Number + Neg<Output = Self> {
+    fn signum(self) -> Self;
+}
+
+macro_rules! unsigned {
+    ($($type:ty),*) => {$(
+        impl Number for $type {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_abs_diff(self, other: Self) -> Option<Self> {
+                Some(<$type>::abs_diff(self, other))
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$type>::checked_add(self, other)
+            }
+
+            fn checked_add_signed(self, delta: isize) -> Option<Self> {
+                <$type>::checked_add_signed(self, delta.try_into().ok()?)
+            }
+        }
+    )*};
+}
+
+macro_rules! signed {
+    ($($type:ty),*) => {$(
+        impl Number for $type {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_abs_diff(self, other: Self) -> Option<Self> {
+                Self::try_from(<$type>::abs_diff(self, other)).ok()
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$type>::checked_add(self, other)
+            }
+
+            fn checked_add_signed(self, delta: isize) -> Option<Self> {
+                Self::try_from(delta).ok().and_then(|delta| self.checked_add(delta))
+            }
+        }
+
+        impl Signed for $type {
+            fn signum(self) -> Self {
+                <$type>::signum(self)
+            }
+        }
+    )*};
+}
+
+signed!(i32, i64, isize);
+unsigned!(u32, u64, usize);
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Number> Point<T> {
+    /// The distance along the axes, which has to fit in `T`: see [`Point::checked_manhattan`].
+    pub fn manhattan(self, other: Self) -> T {
+        self.checked_manhattan(other)
+            .expect("the distance fits in the type")
+    }
+
+    /// The distance along the axes, or `None` if it does not fit in `T`.
+    pub fn checked_manhattan(self, other: Self) -> Option<T> {
+        let x = self.x.checked_abs_diff(other.x)?;
+        let y = self.y.checked_abs_diff(other.y)?;
+
+        x.checked_add(y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    pub fn clamp(self, min: T, max: T) -> Self {
+        Self::new(self.x.clamp(min, max), self.y.clamp(min, max))
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    /// Adds `delta`, or returns `None` if the result does not fit in `T`.
+    pub fn checked_add(self, delta: Point<isize>) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add_signed(delta.x)?,
+            self.y.checked_add_signed(delta.y)?,
+        ))
+    }
+
+    pub fn step(self, direction: impl Into<Point<isize>>) -> Option<Self> {
+        self.checked_add(direction.into())
+    }
+
+    /// The orthogonal neighbours that can be represented in `T`.
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(direction))
+    }
+
+    /// The orthogonal and diagonal neighbours that can be represented in `T`.
+    pub fn all_neighbours(self) -> impl Iterator<Item = Self> {
+        Direction8::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(direction))
+    }
+}
+
+impl<T: Signed> Point<T> {
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, factor: T) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Add<Output = T> + Copy> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Sub<Output = T> + Copy> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// Parses the `x,y` form [`Point`] is displayed as.
+impl<T: FromStr> FromStr for Point<T> {
+    type Err = T::Err;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (x, y) = input.split_once(',').unwrap_or((input, ""));
+
+        Ok(Self::new(x.trim().parse()?, y.trim().parse()?))
+    }
+}
+
+/// The orthogonal directions, with `y` growing downwards.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn delta(self) -> Point<isize> {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::Right => Point::new(1, 0),
+            Self::Down => Point::new(0, 1),
+            Self::Left => Point::new(-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.opposite().turn_right()
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn_right().turn_right()
+    }
+}
+
+impl From<Direction> for Point<isize> {
+    fn from(direction: Direction) -> Self {
+        direction.delta()
+    }
+}
+
+/// The orthogonal and diagonal directions, with `y` growing downwards.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    pub fn delta(self) -> Point<isize> {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::UpRight => Point::new(1, -1),
+            Self::Right => Point::new(1, 0),
+            Self::DownRight => Point::new(1, 1),
+            Self::Down => Point::new(0, 1),
+            Self::DownLeft => Point::new(-1, 1),
+            Self::Left => Point::new(-1, 0),
+            Self::UpLeft => Point::new(-1, -1),
+        }
+    }
+}
+
+impl From<Direction8> for Point<isize> {
+    fn from(direction: Direction8) -> Self {
+        direction.delta()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn measures_distances() {
+        let a = Point::new(2, -3);
+        let b = Point::new(-1, 1);
+
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!((b - a).signum(), Point::new(-1, 1));
+        assert_eq!((a * 3).clamp(-4, 4), Point::new(4, -4));
+        assert_eq!(a.to_string().parse(), Ok(a));
+        assert!("2".parse::<Point<i32>>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "the distance fits in the type")]
+    fn refuses_distances_out_of_range() {
+        let (near, far) = (Point::new(-2, 0), Point::new(i32::MAX - 1, 0));
+
+        assert_eq!(near.checked_manhattan(far), None);
+        assert_eq!(Point::new(0, 1).checked_manhattan(far), Some(i32::MAX));
+        assert_eq!(
+            Point::new(i32::MIN, 0).checked_manhattan(Point::default()),
+            None
+        );
+
+        near.manhattan(far);
+    }
+
+    #[test]
+    fn steps_only_within_the_type() {
+        let corner = Point::new(0usize, 0);
+
+        assert_eq!(corner.step(Direction8::DownLeft), None);
+        assert_eq!(corner.step(Direction::Down), Some(Point::new(0, 1)));
+        assert_eq!(
+            corner.neighbours().collect_vec(),
+            [Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    }
+}
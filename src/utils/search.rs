@@ -0,0 +1,264 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    fn neighbours(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node>;
+
+    /// The neighbours with the cost of moving to them, 1 each unless overridden.
+    fn edges(&self, node: &Self::Node) -> impl Iterator<Item = (Self::Node, usize)> {
+        self.neighbours(node).map(|neighbour| (neighbour, 1))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path<N> {
+    pub nodes: Vec<N>,
+    pub cost: usize,
+}
+
+/// Finds the path with the fewest steps from any of `starts` to a node matching `is_goal`.
+pub fn bfs<G, S, F>(graph: &G, starts: S, mut is_goal: F) -> Option<Path<G::Node>>
+where
+    G: Graph,
+    S: IntoIterator<Item = G::Node>,
+    F: FnMut(&G::Node) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if !parents.contains_key(&start) {
+            parents.insert(start.clone(), None);
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, steps)) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(Path {
+                nodes: reconstruct(&parents, node),
+                cost: steps,
+            });
+        }
+
+        for neighbour in graph.neighbours(&node) {
+            if !parents.contains_key(&neighbour) {
+                parents.insert(neighbour.clone(), Some(node.clone()));
+                queue.push_back((neighbour, steps + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// The number of steps from the closest of `starts` to every reachable node.
+pub fn bfs_distances<G, S>(graph: &G, starts: S) -> HashMap<G::Node, usize>
+where
+    G: Graph,
+    S: IntoIterator<Item = G::Node>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let steps = distances[&node];
+
+        for neighbour in graph.neighbours(&node) {
+            if !distances.contains_key(&neighbour) {
+                distances.insert(neighbour.clone(), steps + 1);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Finds the cheapest path from any of `starts` to a node matching `is_goal`.
+pub fn dijkstra<G, S, F>(graph: &G, starts: S, is_goal: F) -> Option<Path<G::Node>>
+where
+    G: Graph,
+    S: IntoIterator<Item = G::Node>,
+    F: FnMut(&G::Node) -> bool,
+{
+    astar(graph, starts, is_goal, |_| 0)
+}
+
+/// The cost of the cheapest path from any of `starts` to every reachable node.
+pub fn dijkstra_distances<G, S>(graph: &G, starts: S) -> HashMap<G::Node, usize>
+where
+    G: Graph,
+    S: IntoIterator<Item = G::Node>,
+{
+    let mut search = BestFirst::new(starts, |_| 0);
+    while search.next(graph).is_some() {}
+
+    search.costs
+}
+
+/// Like [`dijkstra`], guided by a `heuristic` that must never overestimate the remaining cost.
+pub fn astar<G, S, F, H>(
+    graph: &G,
+    starts: S,
+    mut is_goal: F,
+    heuristic: H,
+) -> Option<Path<G::Node>>
+where
+    G: Graph,
+    S: IntoIterator<Item = G::Node>,
+    F: FnMut(&G::Node) -> bool,
+    H: FnMut(&G::Node) -> usize,
+{
+    let mut search = BestFirst::new(starts, heuristic);
+
+    while let Some(node) = search.next(graph) {
+        if is_goal(&node) {
+            return Some(Path {
+                cost: search.costs[&node],
+                nodes: reconstruct(&search.parents, node),
+            });
+        }
+    }
+
+    None
+}
+
+struct BestFirst<N, H> {
+    heuristic: H,
+    costs: HashMap<N, usize>,
+    parents: HashMap<N, Option<N>>,
+    queue: BinaryHeap<Reverse<(usize, usize)>>,
+    queued: Vec<(N, usize)>,
+}
+
+impl<N, H> BestFirst<N, H>
+where
+    N: Clone + Eq + Hash,
+    H: FnMut(&N) -> usize,
+{
+    fn new<S: IntoIterator<Item = N>>(starts: S, heuristic: H) -> Self {
+        let mut search = Self {
+            heuristic,
+            costs: HashMap::new(),
+            parents: HashMap::new(),
+            queue: BinaryHeap::new(),
+            queued: vec![],
+        };
+
+        for start in starts {
+            search.push(start, 0, None);
+        }
+
+        search
+    }
+
+    fn push(&mut self, node: N, cost: usize, parent: Option<N>) {
+        if self.costs.get(&node).is_some_and(|known| *known <= cost) {
+            return;
+        }
+
+        let priority = cost + (self.heuristic)(&node);
+
+        self.costs.insert(node.clone(), cost);
+        self.parents.insert(node.clone(), parent);
+        // Ties are broken by insertion order, so the search is deterministic
+        self.queue.push(Reverse((priority, self.queued.len())));
+        self.queued.push((node, cost));
+    }
+
+    /// Settles the next cheapest node, skipping entries made stale by a cheaper path.
+    fn next<G: Graph<Node = N>>(&mut self, graph: &G) -> Option<N> {
+        while let Some(Reverse((_, index))) = self.queue.pop() {
+            let (node, cost) = self.queued[index].clone();
+
+            if self.costs[&node] < cost {
+                continue;
+            }
+
+            for (neighbour, step) in graph.edges(&node) {
+                self.push(neighbour, cost + step, Some(node.clone()));
+            }
+
+            return Some(node);
+        }
+
+        None
+    }
+}
+
+fn reconstruct<N: Clone + Eq + Hash>(parents: &HashMap<N, Option<N>>, goal: N) -> Vec<N> {
+    let mut nodes = vec![goal];
+
+    while let Some(Some(parent)) = parents.get(nodes.last().unwrap()) {
+        nodes.push(parent.clone());
+    }
+
+    nodes.reverse();
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A line of nodes where every step forward costs the node's value, with a shortcut 0 -> 4.
+    struct Line;
+
+    impl Graph for Line {
+        type Node = usize;
+
+        fn neighbours(&self, node: &usize) -> impl Iterator<Item = usize> {
+            let shortcut = (*node == 0).then_some(4);
+
+            (*node < 5).then_some(node + 1).into_iter().chain(shortcut)
+        }
+
+        fn edges(&self, node: &usize) -> impl Iterator<Item = (usize, usize)> {
+            self.neighbours(node).map(|next| match next {
+                4 if *node == 0 => (next, 20),
+                _ => (next, next),
+            })
+        }
+    }
+
+    #[test]
+    fn bfs_counts_steps() {
+        let path = bfs(&Line, [0], |node| *node == 5).unwrap();
+
+        assert_eq!(path.nodes, [0, 4, 5]);
+        assert_eq!(path.cost, 2);
+        assert_eq!(bfs_distances(&Line, [3, 0])[&2], 2);
+        assert_eq!(bfs(&Line, [5], |node| *node == 0), None);
+    }
+
+    #[test]
+    fn dijkstra_and_astar_follow_costs() {
+        let expected = Path {
+            nodes: vec![0, 1, 2, 3, 4],
+            cost: 10,
+        };
+
+        assert_eq!(
+            dijkstra(&Line, [0], |node| *node == 4),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            astar(&Line, [0], |node| *node == 4, |node| 4 - node.min(&4)),
+            Some(expected)
+        );
+        assert_eq!(dijkstra_distances(&Line, [0])[&5], 15);
+    }
+}